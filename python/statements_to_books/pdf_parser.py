@@ -1,6 +1,10 @@
+import sys
 from pathlib import Path
 from pypdf import PdfReader
 
+# Separates the pages written by `write_pages_of_pdf`; pypdf never emits a NUL character.
+PAGE_SEPARATOR = "\0"
+
 
 def page_count_of_pdf(pdf_file_path: Path | str) -> int:
     reader = PdfReader(pdf_file_path)
@@ -12,3 +16,23 @@ def extract_text_from_pdf(pdf_file_path: Path, page: int) -> str:
     page = reader.pages[page]
     page_text = page.extract_text()
     return page_text
+
+
+def write_pages_of_pdf(pdf_file_path: Path | str) -> None:
+    """Writes the text of every page to stdout, one `ok` or `err` record per page.
+
+    A record is the status line followed by the page text, or by the error message when
+    the page could not be read. Records are separated by `PAGE_SEPARATOR`.
+    """
+    reader = PdfReader(pdf_file_path)
+    records = []
+    for page in reader.pages:
+        try:
+            records.append("ok\n" + page.extract_text())
+        except Exception as e:
+            records.append("err\n" + str(e))
+    sys.stdout.write(PAGE_SEPARATOR.join(records))
+
+
+if __name__ == "__main__":
+    write_pages_of_pdf(sys.argv[1])
@@ -0,0 +1,142 @@
+use crate::errors::SourceLocation;
+use crate::money_utils::{CurrencyMismatch, Money};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Bound;
+
+/// One row of the statement's daily ending balance table.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DailyBalance {
+    pub date: NaiveDate,
+    pub balance: Money,
+    pub location: SourceLocation,
+}
+
+/// A day whose printed ending balance differs from the balance computed from the
+/// beginning balance and the extracted transactions up to and including that day.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BalanceDivergence {
+    pub date: NaiveDate,
+    pub stated: Money,
+    pub computed: Money,
+    /// How much further the computed balance moved away from the stated one on this day.
+    /// Only days with a nonzero change point at a missing or misread transaction; later
+    /// days simply carry the earlier difference forward.
+    pub new_difference: Money,
+    pub location: SourceLocation,
+}
+
+impl fmt::Display for BalanceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: ending balance on {} is {:.2}, transactions give {:.2}",
+            self.location, self.date, self.stated, self.computed
+        )?;
+        if !self.new_difference.is_zero() {
+            write!(f, ", {:.2} of it new that day", self.new_difference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replays the transactions day by day from `begin_bal` and compares each day's balance
+/// with the daily ending balance table.
+pub(crate) fn replay_daily_balances(
+    begin_bal: Money,
+    signed_amounts: &[(NaiveDate, Money)],
+    daily_balances: &[DailyBalance],
+) -> Result<Vec<BalanceDivergence>, CurrencyMismatch> {
+    let mut net_change_by_date: BTreeMap<NaiveDate, Money> = BTreeMap::new();
+    for (date, amount) in signed_amounts {
+        let net_change = net_change_by_date
+            .entry(*date)
+            .or_insert(Money::zero(begin_bal.currency));
+        *net_change = net_change.checked_add(*amount)?;
+    }
+
+    let mut sorted_daily_balances: Vec<&DailyBalance> = daily_balances.iter().collect();
+    sorted_daily_balances.sort_by_key(|daily_balance| daily_balance.date);
+
+    let mut divergences: Vec<BalanceDivergence> = vec![];
+    let mut computed = begin_bal;
+    let mut replayed_through: Option<NaiveDate> = None;
+    let mut previous_difference = Money::zero(begin_bal.currency);
+    for daily_balance in sorted_daily_balances {
+        let replay_from = match replayed_through {
+            Some(replayed_date) => Bound::Excluded(replayed_date),
+            None => Bound::Unbounded,
+        };
+        let unreplayed =
+            net_change_by_date.range((replay_from, Bound::Included(daily_balance.date)));
+        computed = Money::total(
+            begin_bal.currency,
+            std::iter::once(computed).chain(unreplayed.map(|(_, net_change)| *net_change)),
+        )?;
+        replayed_through = Some(daily_balance.date);
+
+        let difference = daily_balance.balance.checked_sub(computed)?;
+        if !difference.is_zero() {
+            divergences.push(BalanceDivergence {
+                date: daily_balance.date,
+                stated: daily_balance.balance,
+                computed,
+                new_difference: difference.checked_sub(previous_difference)?,
+                location: daily_balance.location.clone(),
+            });
+        }
+        previous_difference = difference;
+    }
+    Ok(divergences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money_utils::Currency;
+    use crate::page_utils::stitch_pages;
+    use crate::period_utils::StatementPeriod;
+    use crate::profile::ParsingProfile;
+    use crate::scanner::StatementScanner;
+    use fastnum::decimal::{Context, Decimal};
+    use fastnum::D256;
+    use std::path::Path;
+
+    fn january_2024() -> StatementPeriod {
+        StatementPeriod {
+            start: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        }
+    }
+
+    fn dec(s: &str) -> Decimal<4> {
+        D256::from_str(s, Context::default()).unwrap()
+    }
+
+    #[test]
+    fn flags_the_day_a_transaction_is_missing() {
+        let page = String::from(
+            "DEPOSITS AND ADDITIONS\n01/05 Client A Deposit $1,000.00\n01/20 Client B Deposit $400.00\nTotal Deposits and Additions $1,400.00\nELECTRONIC WITHDRAWALS\n01/10 Vendor Bill Xfer Ref 1 $100.00\nTotal Electronic Withdrawals $100.00\nDAILY ENDING BALANCE\nDATE AMOUNT DATE AMOUNT\n01/05 $2,000.00 01/10 1,900.00\n01/12 1,850.00 01/20 2,250.00\n",
+        );
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let stitched = stitch_pages(Path::new("sample.pdf"), &[page], &scanner.page_markers);
+        let mut warnings = vec![];
+        let (transactions, daily_balances) =
+            scanner.extract_entries(&stitched, &january_2024(), &mut warnings);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(daily_balances.len(), 4);
+
+        let begin_bal = Money::new(dec("1000.00"), Currency::Usd);
+        let divergences =
+            replay_daily_balances(begin_bal, &transactions.signed_amounts(), &daily_balances)
+                .unwrap();
+        let divergent_dates: Vec<String> = divergences
+            .iter()
+            .map(|divergence| divergence.date.format("%m/%d").to_string())
+            .collect();
+        assert_eq!(divergent_dates, vec!["01/12", "01/20"]);
+        assert_eq!(divergences[0].new_difference.amount, dec("-50.00"));
+        assert!(divergences[1].new_difference.is_zero());
+    }
+}
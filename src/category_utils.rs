@@ -0,0 +1,952 @@
+use crate::ledger_utils::{
+    counter_account, AccountType, ChartOfAccounts, OWNER_CONTRIBUTIONS, OWNER_DRAWS,
+};
+use crate::parse_utils::{
+    ExtractedTransactions, TransactionKind, TransactionKindConst, TransactionSubtype,
+};
+use crate::profile::LocaleSettings;
+use crate::suggest_utils::{LabeledTransaction, NaiveBayes};
+use fastnum::decimal::{Context, Decimal};
+use fastnum::D256;
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+
+/// Where debit card purchases and online payments no rule matches are booked, unless the
+/// rules file names another default.
+pub(crate) const UNCATEGORIZED_EXPENSES: &str = "Uncategorized Expenses";
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RulesError {
+    Io {
+        path: String,
+        message: String,
+    },
+    InvalidLine {
+        line_number: usize,
+        text: String,
+    },
+    /// Rules, or a rule and an account of the default chart, give one category two types.
+    ConflictingTypes {
+        category: String,
+        account_type: AccountType,
+        other_type: AccountType,
+    },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io { path, message } => {
+                write!(f, "category rules not read from {}. {}", path, message)
+            }
+            RulesError::InvalidLine { line_number, text } => {
+                write!(
+                    f,
+                    "category rules line {} not parsed: {:?}",
+                    line_number, text
+                )
+            }
+            RulesError::ConflictingTypes {
+                category,
+                account_type,
+                other_type,
+            } => write!(
+                f,
+                "category rules book {} as {:?} but it is also {:?}",
+                category, account_type, other_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+/// One rule of a rules file. Every condition that is given must hold for the rule to
+/// match.
+#[derive(Debug, Clone)]
+pub(crate) struct CategoryRule {
+    /// The ledger account the matched transactions are booked to.
+    pub category: String,
+    pub account_type: AccountType,
+    /// Rules with a higher priority are tried first. Rules of equal priority are tried in
+    /// file order.
+    pub priority: i64,
+    /// The transaction kinds the rule applies to. A rule that lists none applies to the
+    /// kinds on the side its type implies, debits for expenses and draws and credits for
+    /// income and contributions, or to every kind for other types.
+    pub kinds: Vec<TransactionSubtype>,
+    pub payee_re: Option<Regex>,
+    pub description_re: Option<Regex>,
+    /// Inclusive bounds on the amount as listed on the statement.
+    pub min_amount: Option<Decimal<4>>,
+    pub max_amount: Option<Decimal<4>>,
+}
+
+impl CategoryRule {
+    fn new(category: &str) -> Self {
+        Self {
+            category: String::from(category),
+            account_type: AccountType::Expense,
+            priority: 0,
+            kinds: vec![],
+            payee_re: None,
+            description_re: None,
+            min_amount: None,
+            max_amount: None,
+        }
+    }
+
+    fn matches(
+        &self,
+        subtype: TransactionSubtype,
+        description: &str,
+        payee: &str,
+        amount: Decimal<4>,
+    ) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&subtype))
+            && self
+                .payee_re
+                .as_ref()
+                .is_none_or(|payee_re| payee_re.is_match(payee))
+            && self
+                .description_re
+                .as_ref()
+                .is_none_or(|description_re| description_re.is_match(description))
+            && self
+                .min_amount
+                .is_none_or(|min_amount| amount >= min_amount)
+            && self
+                .max_amount
+                .is_none_or(|max_amount| amount <= max_amount)
+    }
+}
+
+/// Whether `category` is owner's draws or owner's contributions, which are always equity.
+fn is_owner_category(category: &str) -> bool {
+    category == OWNER_DRAWS || category == OWNER_CONTRIBUTIONS
+}
+
+/// Finds the counterparty named in a transaction description, such as the merchant of a
+/// card purchase or the originator of an ACH deposit.
+#[derive(Debug, Clone)]
+pub(crate) struct PayeePatterns {
+    payee_res: Vec<Regex>,
+}
+
+impl PayeePatterns {
+    /// The layouts of descriptions whose dates are printed as in `locale`.
+    pub fn new(locale: &LocaleSettings) -> Self {
+        let date = locale.date_pattern();
+        let payee_res = [
+            format!(r"Card Purchase(?: With Pin)? {date} (?<payee>.+?) Card \d{{4}}"),
+            String::from(r"Orig CO Name:(?<payee>.+?) Orig ID"),
+            String::from(r"Online Transfer To (?<payee>.+?) Transaction#"),
+            format!(r"^(?:{date} )?(?<payee>.+?) (?:Bill Xfer|ACH Pmt|Zelle Payment)"),
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).unwrap())
+        .collect();
+        Self { payee_res }
+    }
+
+    /// The payee, or the whole description if it is in no known layout.
+    pub fn payee<'a>(&self, description: &'a str) -> &'a str {
+        self.payee_res
+            .iter()
+            .find_map(|payee_re| payee_re.captures(description))
+            .and_then(|captures| captures.name("payee"))
+            .map_or(description, |payee| payee.as_str())
+    }
+}
+
+/// Rules that book transactions to categories such as Software, Rent or Contractors
+/// instead of one account per transaction kind.
+///
+/// A rules file has an optional `default = <category>` line, naming where unmatched debit
+/// card purchases and online payments go, followed by one block per rule:
+///
+/// ```text
+/// [Software]
+/// priority = 10
+/// kind = DebitCardPurchase, OnlinePayment
+/// payee = (?i)github|adobe
+/// description = Recurring
+/// min_amount = 0.01
+/// max_amount = 500.00
+/// type = expense
+/// ```
+///
+/// Every line in a block is optional. `payee` and `description` are regexes. `type` is
+/// `expense`, `income`, `asset`, `liability` or `equity`, and is `income` by default only
+/// for rules limited to deposits. Without a `kind`, an expense rule matches only debits and
+/// an income rule only credits, so a refund is not booked as the purchase it reverses. Every
+/// rule for one category must give it the same type, which must also be the type of the
+/// default account of that name, if any.
+///
+/// Money the owner takes out of or puts into the business is classified by rules for the
+/// `[Owner's Draws]` and `[Owner's Contributions]` categories, which are always equity:
+///
+/// ```text
+/// [Owner's Draws]
+/// kind = TransferOut
+/// payee = (?i)personal sav
+///
+/// [Owner's Contributions]
+/// kind = Deposit
+/// description = Online Transfer From Chk \.\.\.1234
+/// ```
+///
+/// Transfers out that no rule matches are held as unclassified transfers.
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone)]
+pub(crate) struct Categorizer {
+    rules: Vec<CategoryRule>,
+    default_category: Option<String>,
+    payee_patterns: PayeePatterns,
+    /// A model trained on hand-labeled transactions, and the confidence above which its
+    /// suggestion is applied to a transaction no rule matches.
+    suggestions: Option<(NaiveBayes, f64)>,
+    /// The hand-labeled transactions the model was trained on.
+    confirmed_labels: Vec<LabeledTransaction>,
+}
+
+impl Default for Categorizer {
+    /// No rules, so every transaction is booked to the account for its kind.
+    fn default() -> Self {
+        Self {
+            rules: vec![],
+            default_category: None,
+            payee_patterns: PayeePatterns::new(&LocaleSettings::us()),
+            suggestions: None,
+            confirmed_labels: vec![],
+        }
+    }
+}
+
+impl Categorizer {
+    pub fn load(path: &Path) -> Result<Self, RulesError> {
+        let rules_str = std::fs::read_to_string(path).map_err(|e| RulesError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&rules_str)
+    }
+
+    /// Reads payees from descriptions whose dates are printed as in `locale`.
+    pub fn with_locale(mut self, locale: &LocaleSettings) -> Self {
+        self.payee_patterns = PayeePatterns::new(locale);
+        self
+    }
+
+    pub fn parse(rules_str: &str) -> Result<Self, RulesError> {
+        let mut categorizer = Categorizer {
+            default_category: Some(String::from(UNCATEGORIZED_EXPENSES)),
+            ..Categorizer::default()
+        };
+        let mut explicit_types: Vec<bool> = vec![];
+        for (line_index, line) in rules_str.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = || RulesError::InvalidLine {
+                line_number: line_index + 1,
+                text: String::from(line),
+            };
+            if let Some(category) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if category.trim().is_empty() {
+                    return Err(invalid_line());
+                }
+                categorizer.rules.push(CategoryRule::new(category.trim()));
+                explicit_types.push(false);
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(invalid_line)?;
+            let value = value.trim();
+            let Some(rule) = categorizer.rules.last_mut() else {
+                match key.trim() {
+                    "default" if !value.is_empty() => {
+                        categorizer.default_category = Some(String::from(value))
+                    }
+                    _ => return Err(invalid_line()),
+                }
+                continue;
+            };
+            let parse_amount =
+                |value: &str| D256::from_str(value, Context::default()).map_err(|_| invalid_line());
+            match key.trim() {
+                "priority" => rule.priority = value.parse().map_err(|_| invalid_line())?,
+                "kind" => {
+                    rule.kinds = value
+                        .split(',')
+                        .map(|kind| kind.trim().parse())
+                        .collect::<Result<Vec<TransactionSubtype>, String>>()
+                        .map_err(|_| invalid_line())?
+                }
+                "payee" => rule.payee_re = Some(Regex::new(value).map_err(|_| invalid_line())?),
+                "description" => {
+                    rule.description_re = Some(Regex::new(value).map_err(|_| invalid_line())?)
+                }
+                "min_amount" => rule.min_amount = Some(parse_amount(value)?),
+                "max_amount" => rule.max_amount = Some(parse_amount(value)?),
+                "type" => {
+                    rule.account_type = value.parse().map_err(|_| invalid_line())?;
+                    *explicit_types.last_mut().unwrap() = true;
+                }
+                _ => return Err(invalid_line()),
+            }
+        }
+        for (rule, explicit_type) in categorizer.rules.iter_mut().zip(explicit_types) {
+            if is_owner_category(&rule.category) {
+                rule.account_type = AccountType::Equity;
+            } else if !explicit_type
+                && !rule.kinds.is_empty()
+                && rule
+                    .kinds
+                    .iter()
+                    .all(|kind| kind.debit_or_credit() == TransactionKind::Credit)
+            {
+                rule.account_type = AccountType::Revenue;
+            }
+            if rule.kinds.is_empty() {
+                let direction = match (rule.category.as_str(), rule.account_type) {
+                    (OWNER_DRAWS, _) | (_, AccountType::Expense) => Some(TransactionKind::Debit),
+                    (OWNER_CONTRIBUTIONS, _) | (_, AccountType::Revenue) => {
+                        Some(TransactionKind::Credit)
+                    }
+                    _ => None,
+                };
+                if let Some(direction) = direction {
+                    rule.kinds = TransactionSubtype::ALL
+                        .into_iter()
+                        .filter(|kind| kind.debit_or_credit() == direction)
+                        .collect();
+                }
+            }
+        }
+        categorizer.check_category_types()?;
+        // A stable sort keeps rules of equal priority in file order.
+        categorizer
+            .rules
+            .sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+        Ok(categorizer)
+    }
+
+    /// Applies the category a model trained on `labels` suggests for a transaction no rule
+    /// matches, when its confidence is at least `threshold`.
+    ///
+    /// The labeled categories are checked like those of the rules: owner's draws may only
+    /// be labeled on debits and owner's contributions on credits, and every other category
+    /// must be booked as one type, which is also that of the default account of its name.
+    pub fn with_suggestions(
+        mut self,
+        labels: Vec<LabeledTransaction>,
+        threshold: f64,
+    ) -> Result<Self, RulesError> {
+        for label in &labels {
+            let (owner_side, labeled_type) = match label.kind.debit_or_credit() {
+                TransactionKind::Debit => (OWNER_DRAWS, AccountType::Expense),
+                TransactionKind::Credit => (OWNER_CONTRIBUTIONS, AccountType::Revenue),
+            };
+            if is_owner_category(&label.category) && label.category != owner_side {
+                return Err(RulesError::ConflictingTypes {
+                    category: label.category.clone(),
+                    account_type: labeled_type,
+                    other_type: AccountType::Equity,
+                });
+            }
+        }
+        self.suggestions = Some((NaiveBayes::train(&labels), threshold));
+        self.confirmed_labels = labels;
+        self.check_category_types()?;
+        Ok(self)
+    }
+
+    /// Every category with the type it is booked as: those of the rules, then those the
+    /// model suggests, then the default category.
+    fn categories(&self) -> impl Iterator<Item = (&str, AccountType)> {
+        let suggested = self
+            .suggestions
+            .iter()
+            .flat_map(|(model, _)| model.categories())
+            .map(|(category, account_type)| {
+                if is_owner_category(category) {
+                    (category, AccountType::Equity)
+                } else {
+                    (category, account_type)
+                }
+            });
+        self.rules
+            .iter()
+            .map(|rule| (rule.category.as_str(), rule.account_type))
+            .chain(suggested)
+            .chain(
+                self.default_category
+                    .as_deref()
+                    .map(|category| (category, AccountType::Expense)),
+            )
+    }
+
+    /// Checks that every category is booked as one type, which is also the type of the
+    /// default account of that name, if any.
+    fn check_category_types(&self) -> Result<(), RulesError> {
+        let mut chart = ChartOfAccounts::default();
+        for (category, account_type) in self.categories() {
+            match chart.get(category) {
+                Some(account) if account.account_type != account_type => {
+                    return Err(RulesError::ConflictingTypes {
+                        category: String::from(category),
+                        account_type,
+                        other_type: account.account_type,
+                    })
+                }
+                Some(_) => {}
+                None => chart.add(category, account_type),
+            }
+        }
+        Ok(())
+    }
+
+    /// The default chart of accounts with the account of every category added.
+    pub fn chart_of_accounts(&self) -> ChartOfAccounts {
+        let mut chart = ChartOfAccounts::default();
+        self.add_accounts(&mut chart);
+        chart
+    }
+
+    /// Adds the account of every category to `chart`.
+    pub fn add_accounts(&self, chart: &mut ChartOfAccounts) {
+        for (category, account_type) in self.categories() {
+            chart.add(category, account_type);
+        }
+    }
+
+    /// The category of `transaction`: that of the first matching rule, else the suggested
+    /// one if confident enough, else the default for its kind. `None` books it to the
+    /// account for its kind.
+    pub fn category<T: TransactionKindConst>(
+        &self,
+        subtype: TransactionSubtype,
+        transaction: &T,
+    ) -> Option<&str> {
+        let description = description(transaction);
+        self.rule_category(subtype, &description, transaction.amount().amount)
+            .or_else(|| {
+                self.suggest(subtype, transaction)
+                    .filter(|suggestion| suggestion.applied)
+                    .map(|suggestion| suggestion.category)
+            })
+            .or(match subtype {
+                TransactionSubtype::DebitCardPurchase | TransactionSubtype::OnlinePayment => {
+                    self.default_category.as_deref()
+                }
+                _ => None,
+            })
+    }
+
+    fn rule_category(
+        &self,
+        subtype: TransactionSubtype,
+        description: &str,
+        amount: Decimal<4>,
+    ) -> Option<&str> {
+        let payee = self.payee_patterns.payee(description);
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(subtype, description, payee, amount))
+            .map(|rule| rule.category.as_str())
+    }
+
+    /// The category the model suggests for a transaction no rule matches.
+    pub fn suggest<T: TransactionKindConst>(
+        &self,
+        subtype: TransactionSubtype,
+        transaction: &T,
+    ) -> Option<AppliedSuggestion<'_>> {
+        let (model, threshold) = self.suggestions.as_ref()?;
+        let description = description(transaction);
+        let amount = transaction.amount().amount;
+        if self.rule_category(subtype, &description, amount).is_some() {
+            return None;
+        }
+        let suggestion = model.suggest(subtype, &description, amount)?;
+        Some(AppliedSuggestion {
+            category: suggestion.category,
+            confidence: suggestion.confidence,
+            applied: suggestion.confidence >= *threshold,
+        })
+    }
+
+    /// Every transaction with the category it is booked to, as lines of a labels file
+    /// to correct by hand and train on, and whether that category is confirmed: set by a
+    /// rule or by a hand label for the same transaction. The others are booked to the
+    /// default account for their kind or by a suggestion, and training on them would only
+    /// teach the model its own guesses.
+    pub fn labels(&self, transactions: &ExtractedTransactions) -> Vec<(LabeledTransaction, bool)> {
+        fn label_list<T: TransactionKindConst>(
+            categorizer: &Categorizer,
+            subtype: TransactionSubtype,
+            transactions: &[T],
+        ) -> Vec<(LabeledTransaction, bool)> {
+            transactions
+                .iter()
+                .map(|transaction| {
+                    let description = description(transaction);
+                    let amount = transaction.amount().amount;
+                    let confirmed_category = categorizer
+                        .rule_category(subtype, &description, amount)
+                        .or_else(|| {
+                            categorizer
+                                .confirmed_labels
+                                .iter()
+                                .find(|label| {
+                                    label.kind == subtype
+                                        && label.amount == amount
+                                        && label.description == description
+                                })
+                                .map(|label| label.category.as_str())
+                        });
+                    let category = confirmed_category.unwrap_or_else(|| {
+                        categorizer
+                            .category(subtype, transaction)
+                            .unwrap_or(counter_account(subtype))
+                    });
+                    let label = LabeledTransaction {
+                        category: String::from(category),
+                        kind: subtype,
+                        amount,
+                        description,
+                    };
+                    (label, confirmed_category.is_some())
+                })
+                .collect()
+        }
+        let mut labels = label_list(self, TransactionSubtype::Deposit, &transactions.deposits);
+        labels.extend(label_list(
+            self,
+            TransactionSubtype::DebitCardPurchase,
+            &transactions.card_purchases,
+        ));
+        labels.extend(label_list(
+            self,
+            TransactionSubtype::OnlinePayment,
+            &transactions.payments,
+        ));
+        labels.extend(label_list(
+            self,
+            TransactionSubtype::TransferOut,
+            &transactions.transfers_out,
+        ));
+        labels.extend(label_list(
+            self,
+            TransactionSubtype::Fee,
+            &transactions.fees,
+        ));
+        labels
+    }
+}
+
+/// A model suggestion for one transaction, and whether its confidence reached the
+/// threshold for applying it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AppliedSuggestion<'a> {
+    pub category: &'a str,
+    pub confidence: f64,
+    pub applied: bool,
+}
+
+/// The transaction text on one line.
+fn description<T: TransactionKindConst>(transaction: &T) -> String {
+    transaction
+        .source()
+        .raw_text
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::SourceLocation;
+    use crate::ledger_utils::{BANK_ACCOUNT, SALES_REVENUE};
+    use crate::money_utils::{Currency, Money};
+    use crate::parse_utils::{DebitCardPurchase, Deposit, OnlinePayment, TransferOut};
+    use crate::provenance::Provenance;
+    use crate::section_utils::StatementSection;
+    use crate::suggest_utils::parse_labels;
+    use chrono::NaiveDate;
+    use std::path::PathBuf;
+
+    fn transaction<T: TransactionKindConst>(raw_text: &str, amount: &str) -> T {
+        let location = SourceLocation {
+            file: PathBuf::from("statement.pdf"),
+            page: 1,
+            byte_offset: 0,
+        };
+        T::from_parts(
+            NaiveDate::from_ymd_opt(2024, 1, 5).unwrap(),
+            Money::new(
+                D256::from_str(amount, Context::default()).unwrap(),
+                Currency::Usd,
+            ),
+            Provenance {
+                start: location.clone(),
+                end: location,
+                raw_text: String::from(raw_text),
+                section: StatementSection::ElectronicWithdrawals,
+                rule: String::from("test"),
+            },
+        )
+    }
+
+    #[test]
+    fn finds_payee_in_known_layouts() {
+        let payee_patterns = PayeePatterns::new(&LocaleSettings::us());
+        let payee = |description| payee_patterns.payee(description);
+        assert_eq!(
+            payee("01/05 Card Purchase 01/04 Office Depot Card 4821 120.00"),
+            "Office Depot"
+        );
+        assert_eq!(
+            payee("01/05 Orig CO Name:Globex Corp Orig ID:7164579 Descr:Payments"),
+            "Globex Corp"
+        );
+        assert_eq!(
+            payee("01/05 Landlord Props Bill Xfer Ref 20607 $395.25"),
+            "Landlord Props"
+        );
+        assert_eq!(payee("01/05 Deposit 42152"), "01/05 Deposit 42152");
+
+        let european_patterns = PayeePatterns::new(&LocaleSettings::european());
+        assert_eq!(
+            european_patterns.payee("05.01. Card Purchase 04.01. Office Depot Card 4821 120,00"),
+            "Office Depot"
+        );
+        assert_eq!(
+            european_patterns.payee("05.01. Landlord Props Bill Xfer Ref 20607 395,25 €"),
+            "Landlord Props"
+        );
+    }
+
+    #[test]
+    fn highest_priority_matching_rule_wins_and_unmatched_expenses_go_to_default() {
+        let categorizer = Categorizer::parse(
+            "# bookkeeping rules\n\
+             default = Other Expenses\n\
+             [Software]\n\
+             payee = (?i)github|adobe\n\
+             [Equipment]\n\
+             priority = 5\n\
+             kind = DebitCardPurchase\n\
+             min_amount = 1000\n\
+             [Rent]\n\
+             description = Landlord\n\
+             max_amount = 2000.00\n\
+             [Consulting Income]\n\
+             kind = Deposit\n\
+             payee = Globex\n",
+        )
+        .unwrap();
+
+        let github: DebitCardPurchase =
+            transaction("01/05 Card Purchase 01/04 Github Card 4821 12.00", "12.00");
+        assert_eq!(
+            categorizer.category(TransactionSubtype::DebitCardPurchase, &github),
+            Some("Software")
+        );
+        let laptop: DebitCardPurchase = transaction(
+            "01/05 Card Purchase 01/04 Adobe Card 4821 1,500.00",
+            "1500.00",
+        );
+        assert_eq!(
+            categorizer.category(TransactionSubtype::DebitCardPurchase, &laptop),
+            Some("Equipment")
+        );
+        let rent: OnlinePayment =
+            transaction("01/05 Landlord Props Bill Xfer Ref 1 $1,800.00", "1800.00");
+        assert_eq!(
+            categorizer.category(TransactionSubtype::OnlinePayment, &rent),
+            Some("Rent")
+        );
+        let big_rent: OnlinePayment =
+            transaction("01/05 Landlord Props Bill Xfer Ref 1 $2,800.00", "2800.00");
+        assert_eq!(
+            categorizer.category(TransactionSubtype::OnlinePayment, &big_rent),
+            Some("Other Expenses")
+        );
+        let wire: Deposit = transaction(
+            "01/05 Orig CO Name:Globex Corp Orig ID:1 Descr:Payments 900.00",
+            "900.00",
+        );
+        assert_eq!(
+            categorizer.category(TransactionSubtype::Deposit, &wire),
+            Some("Consulting Income")
+        );
+        let draw: TransferOut = transaction("01/05 Online Transfer To Sav 300.00", "300.00");
+        assert_eq!(
+            categorizer.category(TransactionSubtype::TransferOut, &draw),
+            None
+        );
+
+        let mut chart = ChartOfAccounts::default();
+        categorizer.add_accounts(&mut chart);
+        assert_eq!(
+            chart.get("Consulting Income").unwrap().account_type,
+            AccountType::Revenue
+        );
+        assert_eq!(
+            chart.get("Software").unwrap().account_type,
+            AccountType::Expense
+        );
+        assert!(Categorizer::parse("[Rent]\nmax_amount = lots\n").is_err());
+        assert!(Categorizer::parse("[Rent]\nkind = Rent\n").is_err());
+        assert!(Categorizer::parse("payee = Landlord\n").is_err());
+    }
+
+    #[test]
+    fn rules_without_kinds_match_the_side_of_their_type() {
+        let categorizer = Categorizer::parse(
+            "[Office Supplies]\n\
+             description = AMAZON\n\
+             [Marketplace Sales]\n\
+             type = income\n\
+             description = AMAZON\n",
+        )
+        .unwrap();
+
+        let purchase: DebitCardPurchase =
+            transaction("01/05 Card Purchase 01/04 AMAZON Card 4821 42.00", "42.00");
+        assert_eq!(
+            categorizer.category(TransactionSubtype::DebitCardPurchase, &purchase),
+            Some("Office Supplies")
+        );
+        let refund: Deposit = transaction("01/09 AMAZON Refund 42.00", "42.00");
+        assert_eq!(
+            categorizer.category(TransactionSubtype::Deposit, &refund),
+            Some("Marketplace Sales")
+        );
+        let refund_only = Categorizer::parse("[Office Supplies]\ndescription = AMAZON\n").unwrap();
+        assert_eq!(
+            refund_only.category(TransactionSubtype::Deposit, &refund),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_a_category_given_two_types() {
+        assert_eq!(
+            Categorizer::parse("[Rent]\ndescription = Landlord\n[Rent]\ntype = income\n")
+                .unwrap_err(),
+            RulesError::ConflictingTypes {
+                category: String::from("Rent"),
+                account_type: AccountType::Revenue,
+                other_type: AccountType::Expense,
+            }
+        );
+        assert!(matches!(
+            Categorizer::parse("[Bank Fees]\ntype = income\n"),
+            Err(RulesError::ConflictingTypes { .. })
+        ));
+        assert!(matches!(
+            Categorizer::parse("default = Consulting\n[Consulting]\nkind = Deposit\n"),
+            Err(RulesError::ConflictingTypes { .. })
+        ));
+        assert!(
+            Categorizer::parse("[Rent]\nkind = OnlinePayment\n[Rent]\nmax_amount = 2000\n").is_ok()
+        );
+    }
+
+    #[test]
+    fn owner_rules_classify_draws_and_contributions_as_equity() {
+        let categorizer = Categorizer::parse(
+            "[Owner's Draws]\n\
+             kind = TransferOut\n\
+             payee = Sav\n\
+             [Owner's Contributions]\n\
+             kind = Deposit\n\
+             description = Transfer From Chk\n",
+        )
+        .unwrap();
+
+        let draw: TransferOut = transaction(
+            "01/05 Online Transfer To Sav ...4321 Transaction#: 1 300.00",
+            "300.00",
+        );
+        assert_eq!(
+            categorizer.category(TransactionSubtype::TransferOut, &draw),
+            Some(OWNER_DRAWS)
+        );
+        let transfer: TransferOut = transaction(
+            "01/05 Online Transfer To Chk ...9876 Transaction#: 2 200.00",
+            "200.00",
+        );
+        assert_eq!(
+            categorizer.category(TransactionSubtype::TransferOut, &transfer),
+            None
+        );
+        let contribution: Deposit =
+            transaction("01/07 Online Transfer From Chk ...1234 5,000.00", "5000.00");
+        assert_eq!(
+            categorizer.category(TransactionSubtype::Deposit, &contribution),
+            Some(OWNER_CONTRIBUTIONS)
+        );
+
+        let chart = categorizer.chart_of_accounts();
+        for owner_account in [OWNER_DRAWS, OWNER_CONTRIBUTIONS] {
+            assert_eq!(
+                chart.get(owner_account).unwrap().account_type,
+                AccountType::Equity
+            );
+        }
+    }
+
+    #[test]
+    fn applies_confident_suggestions_where_no_rule_matches() {
+        let labels = parse_labels(
+            "Software,DebitCardPurchase,12.00,11/05 Card Purchase 11/04 Github Card 4821 12.00\n\
+             Software,DebitCardPurchase,49.99,12/05 Card Purchase 12/04 Github Card 4821 49.99\n\
+             Fuel,DebitCardPurchase,61.20,11/21 Card Purchase 11/21 Shell Oil Card 4821 61.20\n\
+             Fuel,DebitCardPurchase,48.10,12/21 Card Purchase 12/21 Shell Oil Card 4821 48.10\n",
+        )
+        .unwrap();
+        let categorizer = Categorizer::parse("[Travel]\npayee = Shell\n")
+            .unwrap()
+            .with_suggestions(labels, 0.8)
+            .unwrap();
+
+        let github: DebitCardPurchase =
+            transaction("01/05 Card Purchase 01/04 Github Card 4821 19.00", "19.00");
+        let suggestion = categorizer
+            .suggest(TransactionSubtype::DebitCardPurchase, &github)
+            .unwrap();
+        assert_eq!(suggestion.category, "Software");
+        assert!(suggestion.applied, "{:?}", suggestion);
+        assert_eq!(
+            categorizer.category(TransactionSubtype::DebitCardPurchase, &github),
+            Some("Software")
+        );
+
+        let shell: DebitCardPurchase = transaction(
+            "01/21 Card Purchase 01/21 Shell Oil Card 4821 55.00",
+            "55.00",
+        );
+        assert_eq!(
+            categorizer.suggest(TransactionSubtype::DebitCardPurchase, &shell),
+            None
+        );
+        assert_eq!(
+            categorizer.category(TransactionSubtype::DebitCardPurchase, &shell),
+            Some("Travel")
+        );
+
+        let staples: DebitCardPurchase =
+            transaction("01/09 Card Purchase 01/09 Staples Card 4821 20.95", "20.95");
+        let suggestion = categorizer
+            .suggest(TransactionSubtype::DebitCardPurchase, &staples)
+            .unwrap();
+        assert!(!suggestion.applied, "{:?}", suggestion);
+        assert_eq!(
+            categorizer.category(TransactionSubtype::DebitCardPurchase, &staples),
+            Some(UNCATEGORIZED_EXPENSES)
+        );
+    }
+
+    #[test]
+    fn rejects_suggested_categories_that_conflict_with_system_accounts() {
+        let suggest_from = |labels_str: &str| {
+            Categorizer::parse("[Software]\npayee = Github\n")
+                .unwrap()
+                .with_suggestions(parse_labels(labels_str).unwrap(), 0.5)
+        };
+        assert_eq!(
+            suggest_from("Owner's Draws,Deposit,500.00,01/05 Online Transfer From Sav 500.00\n")
+                .err(),
+            Some(RulesError::ConflictingTypes {
+                category: String::from(OWNER_DRAWS),
+                account_type: AccountType::Revenue,
+                other_type: AccountType::Equity,
+            })
+        );
+        assert_eq!(
+            suggest_from(&format!(
+                "{},OnlinePayment,80.00,01/09 Vendor Bill Xfer Ref 1 80.00\n",
+                BANK_ACCOUNT
+            ))
+            .err(),
+            Some(RulesError::ConflictingTypes {
+                category: String::from(BANK_ACCOUNT),
+                account_type: AccountType::Expense,
+                other_type: AccountType::Asset,
+            })
+        );
+        assert!(matches!(
+            suggest_from("Software,Deposit,12.00,01/05 Github Refund 12.00\n"),
+            Err(RulesError::ConflictingTypes { .. })
+        ));
+
+        let categorizer = suggest_from(
+            "Owner's Draws,TransferOut,500.00,01/11 Online Transfer To Sav ...1234 500.00\n",
+        )
+        .unwrap();
+        assert_eq!(
+            categorizer
+                .chart_of_accounts()
+                .get(OWNER_DRAWS)
+                .unwrap()
+                .account_type,
+            AccountType::Equity
+        );
+    }
+
+    #[test]
+    fn writes_only_confirmed_categories_as_labels() {
+        let labels = parse_labels(
+            "Office Supplies,DebitCardPurchase,42.00,01/05 Card Purchase 01/04 AMAZON Card 4821 42.00\n\
+             Office Supplies,DebitCardPurchase,18.50,02/05 Card Purchase 02/04 AMAZON Card 4821 18.50\n",
+        )
+        .unwrap();
+        let categorizer = Categorizer::parse("[Software]\npayee = Github\n")
+            .unwrap()
+            .with_suggestions(labels, 0.5)
+            .unwrap();
+
+        // A refund reads like the labeled purchases but is never suggested their expense.
+        let refund: Deposit = transaction("03/09 AMAZON Card Purchase Refund 42.00", "42.00");
+        assert_eq!(
+            categorizer.suggest(TransactionSubtype::Deposit, &refund),
+            None
+        );
+        assert_eq!(
+            categorizer.category(TransactionSubtype::Deposit, &refund),
+            None
+        );
+
+        let transactions = ExtractedTransactions {
+            deposits: vec![refund],
+            card_purchases: vec![
+                transaction("01/05 Card Purchase 01/04 AMAZON Card 4821 42.00", "42.00"),
+                transaction("03/05 Card Purchase 03/04 AMAZON Card 4821 27.00", "27.00"),
+                transaction("03/07 Card Purchase 03/06 Github Card 4821 12.00", "12.00"),
+            ],
+            payments: vec![],
+            transfers_out: vec![],
+            fees: vec![],
+        };
+        let written: Vec<(String, bool)> = categorizer
+            .labels(&transactions)
+            .into_iter()
+            .map(|(label, confirmed)| (label.category, confirmed))
+            .collect();
+        assert_eq!(
+            written,
+            vec![
+                (String::from(SALES_REVENUE), false),
+                (String::from("Office Supplies"), true),
+                (String::from("Office Supplies"), false),
+                (String::from("Software"), true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::money_utils::Money;
+use crate::period_utils::StatementPeriod;
+use crate::statement::StatementResult;
+use chrono::{Days, Months, NaiveDate};
+use std::fmt;
+use std::path::PathBuf;
+
+/// A break between two statements of one account that follow each other by period.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ContinuityIssue {
+    /// The later statement does not begin with the earlier one's ending balance.
+    BalanceMismatch {
+        previous: PathBuf,
+        next: PathBuf,
+        ending_bal: Money,
+        begin_bal: Money,
+    },
+    /// One of the two balances was not read, so they could not be compared.
+    BalanceNotChecked { previous: PathBuf, next: PathBuf },
+    /// Days between the two periods no statement covers, with the periods of the
+    /// statements presumed missing.
+    Gap {
+        previous: PathBuf,
+        next: PathBuf,
+        start: NaiveDate,
+        end: NaiveDate,
+        missing: Vec<StatementPeriod>,
+    },
+    /// Days both statements cover, such as a statement processed twice.
+    Overlap {
+        previous: PathBuf,
+        next: PathBuf,
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+}
+
+/// The name a statement goes by, the month and year its period ends in, such as
+/// `February 2024` for January 15 through February 14.
+pub(crate) fn statement_month(period: &StatementPeriod) -> String {
+    period.end.format("%B %Y").to_string()
+}
+
+impl fmt::Display for ContinuityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContinuityIssue::BalanceMismatch {
+                previous,
+                next,
+                ending_bal,
+                begin_bal,
+            } => write!(
+                f,
+                "{} ends at {:.2} but {} begins at {:.2}",
+                previous.display(),
+                ending_bal,
+                next.display(),
+                begin_bal
+            ),
+            ContinuityIssue::BalanceNotChecked { previous, next } => write!(
+                f,
+                "balance carried from {} to {} not checked, balances missing",
+                previous.display(),
+                next.display()
+            ),
+            ContinuityIssue::Gap {
+                previous,
+                next,
+                start,
+                end,
+                missing,
+            } => write!(
+                f,
+                "no statement covers {} through {}, between {} and {}; missing {}",
+                start,
+                end,
+                previous.display(),
+                next.display(),
+                missing
+                    .iter()
+                    .map(statement_month)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            ContinuityIssue::Overlap {
+                previous,
+                next,
+                start,
+                end,
+            } => write!(
+                f,
+                "{} and {} both cover {} through {}",
+                previous.display(),
+                next.display(),
+                start,
+                end
+            ),
+        }
+    }
+}
+
+/// The periods of the monthly statements that would fill the days from `start` through
+/// `end`, each starting on the day after the one before ends.
+fn missing_statements(start: NaiveDate, end: NaiveDate) -> Vec<StatementPeriod> {
+    let mut missing = vec![];
+    let mut period_start = start;
+    while period_start <= end {
+        let next_start = period_start + Months::new(1);
+        missing.push(StatementPeriod {
+            start: period_start,
+            end: (next_start - Days::new(1)).min(end),
+        });
+        period_start = next_start;
+    }
+    missing
+}
+
+/// Checks that the statements of each account, told apart by the account number in their
+/// header and ordered by period, chain together: each period starts the day after the one
+/// before ends, and each beginning balance is the ending balance before it. Statements
+/// without an account number are taken as one account's. Balances are compared only where
+/// periods chain, since a gap or an overlap already explains any difference.
+pub(crate) fn continuity_issues(statements: &[StatementResult]) -> Vec<ContinuityIssue> {
+    let mut ordered: Vec<&StatementResult> = statements.iter().collect();
+    ordered.sort_by_key(|statement| {
+        (
+            statement.account_number.clone(),
+            statement.period.start,
+            statement.period.end,
+        )
+    });
+    let mut issues = vec![];
+    for pair in ordered.windows(2) {
+        let (previous, next) = (pair[0], pair[1]);
+        if previous.account_number != next.account_number {
+            continue;
+        }
+        let day_after = previous.period.end + Days::new(1);
+        if next.period.start > day_after {
+            let end = next.period.start - Days::new(1);
+            issues.push(ContinuityIssue::Gap {
+                previous: previous.file.clone(),
+                next: next.file.clone(),
+                start: day_after,
+                end,
+                missing: missing_statements(day_after, end),
+            });
+            continue;
+        }
+        if next.period.start < day_after {
+            issues.push(ContinuityIssue::Overlap {
+                previous: previous.file.clone(),
+                next: next.file.clone(),
+                start: next.period.start,
+                end: previous.period.end.min(next.period.end),
+            });
+            continue;
+        }
+        match (previous.ending_bal, next.begin_bal) {
+            (Some(ending_bal), Some(begin_bal)) if ending_bal != begin_bal => {
+                issues.push(ContinuityIssue::BalanceMismatch {
+                    previous: previous.file.clone(),
+                    next: next.file.clone(),
+                    ending_bal,
+                    begin_bal,
+                })
+            }
+            (Some(_), Some(_)) => {}
+            _ => issues.push(ContinuityIssue::BalanceNotChecked {
+                previous: previous.file.clone(),
+                next: next.file.clone(),
+            }),
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money_utils::Currency;
+    use crate::profile::ParsingProfile;
+    use crate::scanner::StatementScanner;
+    use crate::statement::parse_statement_pages;
+    use crate::synthetic_utils::{generate_statements, GeneratorSpec};
+    use fastnum::decimal::Context;
+    use fastnum::D256;
+    use std::path::Path;
+
+    fn parsed_statements(spec_str: &str) -> Vec<StatementResult> {
+        parsed_account_statements(spec_str, None)
+    }
+
+    /// The generated statements, with `account_number` added to the first page's header.
+    fn parsed_account_statements(
+        spec_str: &str,
+        account_number: Option<&str>,
+    ) -> Vec<StatementResult> {
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let as_of = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        generate_statements(&GeneratorSpec::parse(spec_str).unwrap())
+            .iter()
+            .map(|statement| {
+                let mut pages = statement.pdf_page_strs.clone();
+                if let Some(account_number) = account_number {
+                    pages[0] = format!("Account Number: {}\n{}", account_number, pages[0]);
+                }
+                parse_statement_pages(
+                    Path::new(&format!("{}.pdf", statement.name)),
+                    &pages,
+                    &scanner,
+                    as_of,
+                    vec![],
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn reports_gaps_by_month_overlaps_and_balance_breaks() {
+        const SPEC: &str = "seed = 5\nstatements = 5\nfirst_period_start = 2024-01-15\n";
+        let mut statements = parsed_statements(SPEC);
+        assert!(continuity_issues(&statements).is_empty());
+        // Statements may arrive in any order.
+        statements.reverse();
+        assert!(continuity_issues(&statements).is_empty());
+
+        let mut with_gap = parsed_statements(SPEC);
+        with_gap.drain(1..3);
+        let issues = continuity_issues(&with_gap);
+        assert_eq!(issues.len(), 1);
+        let ContinuityIssue::Gap {
+            start,
+            end,
+            missing,
+            ..
+        } = &issues[0]
+        else {
+            panic!("{:?}", issues);
+        };
+        assert_eq!((*start, *end), (date(2, 15), date(4, 14)));
+        assert_eq!(
+            missing.iter().map(statement_month).collect::<Vec<_>>(),
+            vec!["March 2024", "April 2024"]
+        );
+        assert!(issues[0]
+            .to_string()
+            .ends_with("missing March 2024, April 2024"));
+
+        let mut with_break = parsed_statements(SPEC);
+        with_break.truncate(2);
+        with_break[1].begin_bal = Some(Money::new(
+            D256::from_str("1.00", Context::default()).unwrap(),
+            Currency::Usd,
+        ));
+        assert!(matches!(
+            continuity_issues(&with_break)[..],
+            [ContinuityIssue::BalanceMismatch { .. }]
+        ));
+
+        let mut twice = parsed_statements(SPEC);
+        twice.truncate(1);
+        twice.extend(parsed_statements(SPEC).into_iter().take(1));
+        assert_eq!(
+            continuity_issues(&twice),
+            vec![ContinuityIssue::Overlap {
+                previous: PathBuf::from("statement_001.pdf"),
+                next: PathBuf::from("statement_001.pdf"),
+                start: date(1, 15),
+                end: date(2, 14),
+            }]
+        );
+    }
+
+    #[test]
+    fn checks_each_account_on_its_own() {
+        let mut statements = parsed_account_statements(
+            "seed = 5\nstatements = 3\nfirst_period_start = 2024-01-15\n",
+            Some("000123456789"),
+        );
+        statements.extend(parsed_account_statements(
+            "seed = 9\nstatements = 3\nfirst_period_start = 2024-01-01\nbeginning_balance = 800.00\n",
+            Some("000987654321"),
+        ));
+        assert_eq!(
+            statements[0].account_number.as_deref(),
+            Some("000123456789")
+        );
+        assert!(continuity_issues(&statements).is_empty());
+
+        // Taken as one account, the two interleave and overlap.
+        for statement in &mut statements {
+            statement.account_number = None;
+        }
+        assert!(!continuity_issues(&statements).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::period_utils::StatementPeriod;
+use crate::section_utils::StatementSection;
+use chrono::NaiveDate;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SourceLocation {
+    pub file: PathBuf,
+    pub page: usize,
+    pub byte_offset: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} page {} byte {}",
+            self.file.display(),
+            self.page,
+            self.byte_offset
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BalanceKind {
+    Beginning,
+    Ending,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ParseError {
+    NoInputFiles {
+        dir: String,
+    },
+    InvalidGlob {
+        pattern: String,
+    },
+    PageCount {
+        file: PathBuf,
+        message: String,
+    },
+    PageExtraction {
+        file: PathBuf,
+        page: usize,
+        message: String,
+    },
+    /// The Python process extracting the pages could not be run, failed, or wrote output
+    /// that could not be read.
+    Extractor {
+        file: PathBuf,
+        message: String,
+    },
+    TextNotWritten {
+        file: PathBuf,
+        message: String,
+    },
+    InvalidDate {
+        location: SourceLocation,
+        text: String,
+    },
+    InvalidAmount {
+        location: SourceLocation,
+        text: String,
+    },
+    InvalidPeriod {
+        location: SourceLocation,
+        text: String,
+    },
+    DateOutsidePeriod {
+        location: SourceLocation,
+        text: String,
+        period: StatementPeriod,
+    },
+    /// A dated entry that could not be read, or that is listed under no transaction section.
+    UnmatchedEntry {
+        location: SourceLocation,
+        text: String,
+        section: Option<StatementSection>,
+    },
+    MissingStatementPeriod {
+        file: PathBuf,
+    },
+    PeriodAfterAsOf {
+        file: PathBuf,
+        period: StatementPeriod,
+        as_of: NaiveDate,
+    },
+    MissingBalance {
+        file: PathBuf,
+        kind: BalanceKind,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::NoInputFiles { dir } => write!(f, "No pdf files found in {}", dir),
+            ParseError::InvalidGlob { pattern } => write!(f, "Invalid glob pattern {}", pattern),
+            ParseError::PageCount { file, message } => {
+                write!(f, "{}: page count not read. {}", file.display(), message)
+            }
+            ParseError::PageExtraction {
+                file,
+                page,
+                message,
+            } => write!(
+                f,
+                "{} page {}: no text extracted. {}",
+                file.display(),
+                page,
+                message
+            ),
+            ParseError::Extractor { file, message } => {
+                write!(f, "{}: page extractor failed. {}", file.display(), message)
+            }
+            ParseError::TextNotWritten { file, message } => {
+                write!(
+                    f,
+                    "{}: extracted text not written. {}",
+                    file.display(),
+                    message
+                )
+            }
+            ParseError::InvalidDate { location, text } => {
+                write!(f, "{}: invalid date {:?}", location, text)
+            }
+            ParseError::InvalidAmount { location, text } => {
+                write!(f, "{}: invalid amount {:?}", location, text)
+            }
+            ParseError::InvalidPeriod { location, text } => {
+                write!(f, "{}: invalid statement period {:?}", location, text)
+            }
+            ParseError::DateOutsidePeriod {
+                location,
+                text,
+                period,
+            } => write!(
+                f,
+                "{}: date {:?} is outside the statement period {}",
+                location, text, period
+            ),
+            ParseError::UnmatchedEntry {
+                location,
+                text,
+                section: Some(section),
+            } => {
+                write!(
+                    f,
+                    "{}: no date and amount found in {} entry {:?}",
+                    location,
+                    section.heading(),
+                    text
+                )
+            }
+            ParseError::UnmatchedEntry {
+                location,
+                text,
+                section: None,
+            } => {
+                write!(
+                    f,
+                    "{}: entry {:?} is under no transaction section and was not read",
+                    location, text
+                )
+            }
+            ParseError::MissingStatementPeriod { file } => {
+                write!(f, "{}: statement period not parsed", file.display())
+            }
+            ParseError::PeriodAfterAsOf {
+                file,
+                period,
+                as_of,
+            } => write!(
+                f,
+                "{}: statement period {} ends after the as-of date {}",
+                file.display(),
+                period,
+                as_of
+            ),
+            ParseError::MissingBalance { file, kind } => {
+                write!(f, "{}: {:?} Balance not parsed", file.display(), kind)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
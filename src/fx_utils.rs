@@ -0,0 +1,191 @@
+use crate::money_utils::{Currency, Money};
+use chrono::NaiveDate;
+use fastnum::decimal::{Context, Decimal};
+use fastnum::D256;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FxError {
+    Io {
+        path: String,
+        message: String,
+    },
+    InvalidLine {
+        line_number: usize,
+        text: String,
+    },
+    MissingRate {
+        from: Currency,
+        to: Currency,
+        date: NaiveDate,
+    },
+}
+
+impl fmt::Display for FxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FxError::Io { path, message } => {
+                write!(f, "exchange rates not read from {}. {}", path, message)
+            }
+            FxError::InvalidLine { line_number, text } => {
+                write!(
+                    f,
+                    "exchange rate line {} not parsed: {:?}",
+                    line_number, text
+                )
+            }
+            FxError::MissingRate { from, to, date } => {
+                write!(f, "no {} to {} rate on or before {}", from, to, date)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FxError {}
+
+/// Exchange rates read from a local file, keyed by date.
+///
+/// Each line of the file is `date,from,to,rate`, for example `2024-01-31,EUR,USD,1.0836`,
+/// meaning one EUR bought 1.0836 USD on that date. Blank lines and lines starting with `#`
+/// are ignored.
+#[derive(Debug, Default)]
+pub(crate) struct ExchangeRates {
+    rates: HashMap<(Currency, Currency), BTreeMap<NaiveDate, Decimal<4>>>,
+}
+
+impl ExchangeRates {
+    pub fn load(path: &Path) -> Result<Self, FxError> {
+        let rates_str = std::fs::read_to_string(path).map_err(|e| FxError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&rates_str)
+    }
+
+    pub fn parse(rates_str: &str) -> Result<Self, FxError> {
+        let mut exchange_rates = ExchangeRates::default();
+        for (line_index, line) in rates_str.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = || FxError::InvalidLine {
+                line_number: line_index + 1,
+                text: String::from(line),
+            };
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [date_str, from_str, to_str, rate_str] = fields[..] else {
+                return Err(invalid_line());
+            };
+            let date =
+                NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|_| invalid_line())?;
+            let from: Currency = from_str.parse().map_err(|_| invalid_line())?;
+            let to: Currency = to_str.parse().map_err(|_| invalid_line())?;
+            let rate = D256::from_str(rate_str, Context::default()).map_err(|_| invalid_line())?;
+            if rate.is_zero() || rate.is_negative() {
+                return Err(invalid_line());
+            }
+            exchange_rates.insert(date, from, to, rate);
+        }
+        Ok(exchange_rates)
+    }
+
+    pub fn insert(&mut self, date: NaiveDate, from: Currency, to: Currency, rate: Decimal<4>) {
+        self.rates.entry((from, to)).or_default().insert(date, rate);
+    }
+
+    /// The most recent rate on or before `date`, using the inverse of a `to`/`from` rate
+    /// when only that direction is listed.
+    pub fn rate(&self, from: Currency, to: Currency, date: NaiveDate) -> Option<Decimal<4>> {
+        if from == to {
+            return Some(Decimal::<4>::ONE);
+        }
+        let latest = |pair: (Currency, Currency)| {
+            self.rates
+                .get(&pair)
+                .and_then(|by_date| by_date.range(..=date).next_back())
+        };
+        match (latest((from, to)), latest((to, from))) {
+            (Some((direct_date, direct)), Some((inverse_date, inverse))) => {
+                if direct_date >= inverse_date {
+                    Some(*direct)
+                } else {
+                    Some(Decimal::<4>::ONE / *inverse)
+                }
+            }
+            (Some((_, direct)), None) => Some(*direct),
+            (None, Some((_, inverse))) => Some(Decimal::<4>::ONE / *inverse),
+            (None, None) => None,
+        }
+    }
+
+    /// Converts `money` into `to` at the rate in effect on `date`.
+    pub fn convert(&self, money: Money, to: Currency, date: NaiveDate) -> Result<Money, FxError> {
+        let rate = self
+            .rate(money.currency, to, date)
+            .ok_or(FxError::MissingRate {
+                from: money.currency,
+                to,
+                date,
+            })?;
+        Ok(Money::new(money.amount * rate, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal<4> {
+        D256::from_str(s, Context::default()).unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn converts_with_latest_rate_on_or_before_date() {
+        let rates = ExchangeRates::parse(
+            "# date,from,to,rate\n2024-01-01,EUR,USD,1.10\n2024-02-01,EUR,USD,1.08\n2024-01-15,USD,CAD,1.25\n",
+        )
+        .unwrap();
+        let eur = Money::new(dec("100.00"), Currency::Eur);
+        assert_eq!(
+            rates.convert(eur, Currency::Usd, date("2024-01-31")),
+            Ok(Money::new(dec("110.00"), Currency::Usd))
+        );
+        assert_eq!(
+            rates.convert(eur, Currency::Usd, date("2024-02-29")),
+            Ok(Money::new(dec("108.00"), Currency::Usd))
+        );
+        let cad = Money::new(dec("125.00"), Currency::Cad);
+        assert_eq!(
+            rates.convert(cad, Currency::Usd, date("2024-01-31")),
+            Ok(Money::new(dec("100.00"), Currency::Usd))
+        );
+        assert_eq!(
+            rates.convert(eur, Currency::Usd, date("2023-12-31")),
+            Err(FxError::MissingRate {
+                from: Currency::Eur,
+                to: Currency::Usd,
+                date: date("2023-12-31")
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert_eq!(
+            ExchangeRates::parse("2024-01-01,EUR,USD").unwrap_err(),
+            FxError::InvalidLine {
+                line_number: 1,
+                text: String::from("2024-01-01,EUR,USD")
+            }
+        );
+        assert!(ExchangeRates::parse("2024-01-01,EUR,XYZ,1.1").is_err());
+        assert!(ExchangeRates::parse("2024-01-01,EUR,USD,0").is_err());
+    }
+}
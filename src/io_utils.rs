@@ -1,12 +1,14 @@
-use glob::{glob, PatternError};
-use std::path::PathBuf;
+use crate::errors::ParseError;
+use glob::glob;
+use std::path::{Path, PathBuf};
 
 pub(crate) fn glob_files_to_process(
     file_dir: &str,
     file_ext: &str,
-) -> Result<Vec<PathBuf>, PatternError> {
+) -> Result<Vec<PathBuf>, ParseError> {
     let mut file_paths: Vec<PathBuf> = Vec::new();
-    match glob(&format!("{}/*.{}", file_dir, file_ext)) {
+    let pattern = format!("{}/*.{}", file_dir, file_ext);
+    match glob(&pattern) {
         Ok(globbed_file_paths) => {
             for fp in globbed_file_paths {
                 match fp {
@@ -14,8 +16,30 @@ pub(crate) fn glob_files_to_process(
                     Err(e) => println!("{:?}", e),
                 }
             }
+            if file_paths.is_empty() {
+                return Err(ParseError::NoInputFiles {
+                    dir: String::from(file_dir),
+                });
+            }
             Ok(file_paths)
         }
-        Err(error) => Err(error),
+        Err(_) => Err(ParseError::InvalidGlob { pattern }),
     }
 }
+
+/// Separates pages in a statement text file, as a form feed would on a printer.
+pub(crate) const PAGE_BREAK: char = '\u{c}';
+
+/// Writes the extracted text of a statement to `<txt_dir>/<pdf file stem>.txt`, one page
+/// after another with [`PAGE_BREAK`] between them.
+pub(crate) fn write_statement_text(
+    txt_dir: &Path,
+    pdf_file_path: &Path,
+    pdf_page_strs: &[String],
+) -> std::io::Result<PathBuf> {
+    let file_stem = pdf_file_path.file_stem().unwrap_or_default();
+    let txt_file_path = txt_dir.join(file_stem).with_extension("txt");
+    std::fs::create_dir_all(txt_dir)?;
+    std::fs::write(&txt_file_path, pdf_page_strs.join(&PAGE_BREAK.to_string()))?;
+    Ok(txt_file_path)
+}
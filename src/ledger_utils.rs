@@ -0,0 +1,780 @@
+use crate::category_utils::Categorizer;
+use crate::money_utils::{Currency, Money};
+use crate::parse_utils::{ExtractedTransactions, TransactionKindConst, TransactionSubtype};
+use crate::provenance::Provenance;
+use chrono::NaiveDate;
+use fastnum::decimal::{Context, Decimal};
+use fastnum::D256;
+use std::fmt;
+use std::path::Path;
+
+/// The ledger account the statement's own bank account is booked to.
+pub(crate) const BANK_ACCOUNT: &str = "Business Checking";
+pub(crate) const OPENING_BALANCE_EQUITY: &str = "Opening Balance Equity";
+pub(crate) const OWNER_CONTRIBUTIONS: &str = "Owner's Contributions";
+pub(crate) const OWNER_DRAWS: &str = "Owner's Draws";
+/// Where transfers out no rule classifies are held, since the statement does not say
+/// whether the money went to the owner or to another account of the business. It is a
+/// suspense account that reduces equity like owner's draws, so the transfers stay
+/// financing cash flows until rules classify them.
+pub(crate) const UNCLASSIFIED_TRANSFERS: &str = "Unclassified Transfers";
+pub(crate) const SALES_REVENUE: &str = "Sales Revenue";
+pub(crate) const CARD_PURCHASES: &str = "Card Purchases";
+pub(crate) const ONLINE_PAYMENTS: &str = "Online Payments";
+pub(crate) const BANK_FEES: &str = "Bank Fees";
+pub(crate) const CREDIT_CARD_PAYABLE: &str = "Credit Card Payable";
+pub(crate) const LOANS_PAYABLE: &str = "Loans Payable";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EntrySide {
+    Debit,
+    Credit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccountType {
+    Asset,
+    Liability,
+    Equity,
+    Revenue,
+    Expense,
+}
+
+impl AccountType {
+    /// The side that increases an account of this type.
+    pub fn normal_side(&self) -> EntrySide {
+        match self {
+            AccountType::Asset | AccountType::Expense => EntrySide::Debit,
+            AccountType::Liability | AccountType::Equity | AccountType::Revenue => {
+                EntrySide::Credit
+            }
+        }
+    }
+}
+
+/// Reads the account type names used in rules and opening balance files: `asset`,
+/// `liability`, `equity`, `income` and `expense`.
+impl std::str::FromStr for AccountType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "asset" => Ok(AccountType::Asset),
+            "liability" => Ok(AccountType::Liability),
+            "equity" => Ok(AccountType::Equity),
+            "income" => Ok(AccountType::Revenue),
+            "expense" => Ok(AccountType::Expense),
+            other => Err(format!("unknown account type {:?}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Account {
+    pub name: String,
+    pub account_type: AccountType,
+    /// The side that increases the account. Contra accounts, such as owner's draws within
+    /// equity, increase on the side opposite to their type.
+    pub normal_side: EntrySide,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LedgerError {
+    UnknownAccount {
+        name: String,
+    },
+    Unbalanced {
+        description: String,
+        debits: Money,
+        credits: Money,
+    },
+    CurrencyMismatch {
+        description: String,
+        ledger: Currency,
+        entry: Currency,
+    },
+    /// An opening balance names an account of the chart as another type.
+    ConflictingAccountType {
+        name: String,
+        account_type: AccountType,
+        chart_type: AccountType,
+    },
+    /// An opening balance is given for the bank account, whose beginning balance is read
+    /// from the statements.
+    BankAccountOpeningBalance,
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::UnknownAccount { name } => {
+                write!(f, "no account {:?} in the chart of accounts", name)
+            }
+            LedgerError::Unbalanced {
+                description,
+                debits,
+                credits,
+            } => write!(
+                f,
+                "journal entry {:?} not balanced, debits {:.2} and credits {:.2}",
+                description, debits, credits
+            ),
+            LedgerError::CurrencyMismatch {
+                description,
+                ledger,
+                entry,
+            } => write!(
+                f,
+                "journal entry {:?} in {} cannot be posted to a {} ledger",
+                description, entry, ledger
+            ),
+            LedgerError::ConflictingAccountType {
+                name,
+                account_type,
+                chart_type,
+            } => write!(
+                f,
+                "opening balance books {} as {:?} but it is {:?} in the chart of accounts",
+                name, account_type, chart_type
+            ),
+            LedgerError::BankAccountOpeningBalance => write!(
+                f,
+                "opening balance given for {}, whose beginning balance is read from the statements",
+                BANK_ACCOUNT
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+/// The accounts journal entries may be posted to.
+#[derive(Debug, Clone)]
+pub(crate) struct ChartOfAccounts {
+    accounts: Vec<Account>,
+}
+
+impl Default for ChartOfAccounts {
+    /// The bank account, a credit card and a loan, the equity accounts, and an account
+    /// for each kind of transaction the scanner extracts.
+    fn default() -> Self {
+        let mut chart = ChartOfAccounts { accounts: vec![] };
+        chart.add(BANK_ACCOUNT, AccountType::Asset);
+        chart.add(CREDIT_CARD_PAYABLE, AccountType::Liability);
+        chart.add(LOANS_PAYABLE, AccountType::Liability);
+        chart.add(OPENING_BALANCE_EQUITY, AccountType::Equity);
+        chart.add(OWNER_CONTRIBUTIONS, AccountType::Equity);
+        chart.add_contra(OWNER_DRAWS, AccountType::Equity);
+        chart.add_contra(UNCLASSIFIED_TRANSFERS, AccountType::Equity);
+        chart.add(SALES_REVENUE, AccountType::Revenue);
+        chart.add(CARD_PURCHASES, AccountType::Expense);
+        chart.add(ONLINE_PAYMENTS, AccountType::Expense);
+        chart.add(BANK_FEES, AccountType::Expense);
+        chart
+    }
+}
+
+impl ChartOfAccounts {
+    /// Adds an account, unless one of that name is already in the chart.
+    pub fn add(&mut self, name: &str, account_type: AccountType) {
+        self.push(name, account_type, account_type.normal_side());
+    }
+
+    /// Adds a contra account, which reduces the other accounts of its type.
+    pub fn add_contra(&mut self, name: &str, account_type: AccountType) {
+        let normal_side = match account_type.normal_side() {
+            EntrySide::Debit => EntrySide::Credit,
+            EntrySide::Credit => EntrySide::Debit,
+        };
+        self.push(name, account_type, normal_side);
+    }
+
+    fn push(&mut self, name: &str, account_type: AccountType, normal_side: EntrySide) {
+        if self.get(name).is_none() {
+            self.accounts.push(Account {
+                name: String::from(name),
+                account_type,
+                normal_side,
+            });
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.accounts.iter().find(|account| account.name == name)
+    }
+
+    /// The accounts in the order they were added.
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+}
+
+/// One line of a journal entry. The amount is never negative; the side says which way
+/// it moves the account.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Posting {
+    pub account: String,
+    pub side: EntrySide,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JournalEntry {
+    pub date: NaiveDate,
+    pub description: String,
+    pub postings: Vec<Posting>,
+    /// The statement text the entry was booked from, if it was booked from a transaction.
+    pub source: Option<Provenance>,
+}
+
+impl JournalEntry {
+    /// An entry moving `amount` from the `credit_account` to the `debit_account`. A
+    /// negative amount, such as a refund, reverses the sides.
+    pub fn transfer(
+        date: NaiveDate,
+        description: &str,
+        debit_account: &str,
+        credit_account: &str,
+        amount: Money,
+    ) -> Self {
+        let (debit_account, credit_account, amount) = if amount.amount.is_negative() {
+            (credit_account, debit_account, -amount)
+        } else {
+            (debit_account, credit_account, amount)
+        };
+        JournalEntry {
+            date,
+            description: String::from(description),
+            postings: vec![
+                Posting {
+                    account: String::from(debit_account),
+                    side: EntrySide::Debit,
+                    amount,
+                },
+                Posting {
+                    account: String::from(credit_account),
+                    side: EntrySide::Credit,
+                    amount,
+                },
+            ],
+            source: None,
+        }
+    }
+
+    fn side_total(&self, side: EntrySide, currency: Currency) -> Money {
+        self.postings
+            .iter()
+            .filter(|posting| posting.side == side)
+            .fold(Money::zero(currency), |total, posting| {
+                Money::new(total.amount + posting.amount.amount, currency)
+            })
+    }
+}
+
+/// The account an extracted transaction of `subtype` is booked against, opposite the
+/// bank account.
+pub(crate) fn counter_account(subtype: TransactionSubtype) -> &'static str {
+    match subtype {
+        TransactionSubtype::Deposit => SALES_REVENUE,
+        TransactionSubtype::DebitCardPurchase => CARD_PURCHASES,
+        TransactionSubtype::OnlinePayment => ONLINE_PAYMENTS,
+        TransactionSubtype::TransferOut => UNCLASSIFIED_TRANSFERS,
+        TransactionSubtype::Fee => BANK_FEES,
+    }
+}
+
+/// The balance of an account the statements do not show, such as a loan, at the start of
+/// the books.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OpeningBalance {
+    pub account: String,
+    pub account_type: AccountType,
+    /// The balance on the account's normal side.
+    pub amount: Decimal<4>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum OpeningBalancesError {
+    Io { path: String, message: String },
+    InvalidLine { line_number: usize, text: String },
+}
+
+impl fmt::Display for OpeningBalancesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpeningBalancesError::Io { path, message } => {
+                write!(f, "opening balances not read from {}. {}", path, message)
+            }
+            OpeningBalancesError::InvalidLine { line_number, text } => {
+                write!(
+                    f,
+                    "opening balances line {} not parsed: {:?}",
+                    line_number, text
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OpeningBalancesError {}
+
+pub(crate) fn load_opening_balances(
+    path: &Path,
+) -> Result<Vec<OpeningBalance>, OpeningBalancesError> {
+    let balances_str = std::fs::read_to_string(path).map_err(|e| OpeningBalancesError::Io {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    })?;
+    parse_opening_balances(&balances_str)
+}
+
+/// Reads `account,type,amount` lines, for example `Loans Payable,liability,25000.00`.
+/// Blank lines and lines starting with `#` are ignored.
+pub(crate) fn parse_opening_balances(
+    balances_str: &str,
+) -> Result<Vec<OpeningBalance>, OpeningBalancesError> {
+    let mut opening_balances = vec![];
+    for (line_index, line) in balances_str.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid_line = || OpeningBalancesError::InvalidLine {
+            line_number: line_index + 1,
+            text: String::from(line),
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [account, account_type, amount] = fields[..] else {
+            return Err(invalid_line());
+        };
+        if account.is_empty() {
+            return Err(invalid_line());
+        }
+        opening_balances.push(OpeningBalance {
+            account: String::from(account),
+            account_type: account_type.parse().map_err(|_| invalid_line())?,
+            amount: D256::from_str(amount, Context::default()).map_err(|_| invalid_line())?,
+        });
+    }
+    Ok(opening_balances)
+}
+
+/// Journal entries in one currency, posted against a chart of accounts.
+#[derive(Debug, Clone)]
+pub(crate) struct Ledger {
+    pub chart: ChartOfAccounts,
+    pub currency: Currency,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Ledger {
+    pub fn new(chart: ChartOfAccounts, currency: Currency) -> Self {
+        Self {
+            chart,
+            currency,
+            entries: vec![],
+        }
+    }
+
+    /// Adds `entry` once its accounts are known, its postings are in the ledger's
+    /// currency, and its debits equal its credits.
+    pub fn post(&mut self, entry: JournalEntry) -> Result<(), LedgerError> {
+        for posting in &entry.postings {
+            if self.chart.get(&posting.account).is_none() {
+                return Err(LedgerError::UnknownAccount {
+                    name: posting.account.clone(),
+                });
+            }
+            if posting.amount.currency != self.currency {
+                return Err(LedgerError::CurrencyMismatch {
+                    description: entry.description.clone(),
+                    ledger: self.currency,
+                    entry: posting.amount.currency,
+                });
+            }
+        }
+        let debits = entry.side_total(EntrySide::Debit, self.currency);
+        let credits = entry.side_total(EntrySide::Credit, self.currency);
+        if debits != credits {
+            return Err(LedgerError::Unbalanced {
+                description: entry.description,
+                debits,
+                credits,
+            });
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Books the beginning balance of the bank account against opening balance equity.
+    pub fn post_opening_balance(
+        &mut self,
+        date: NaiveDate,
+        begin_bal: Money,
+    ) -> Result<(), LedgerError> {
+        self.post(JournalEntry::transfer(
+            date,
+            "Beginning balance",
+            BANK_ACCOUNT,
+            OPENING_BALANCE_EQUITY,
+            begin_bal,
+        ))
+    }
+
+    /// Books the balance of each account in `opening_balances` on `date` against opening
+    /// balance equity, adding any account missing from the chart.
+    ///
+    /// An account already in the chart must be given its type there. The bank account is
+    /// refused, since its beginning balance is booked from the earliest statement.
+    pub fn post_opening_balances(
+        &mut self,
+        date: NaiveDate,
+        opening_balances: &[OpeningBalance],
+    ) -> Result<(), LedgerError> {
+        for opening_balance in opening_balances {
+            if opening_balance.account == BANK_ACCOUNT {
+                return Err(LedgerError::BankAccountOpeningBalance);
+            }
+            match self.chart.get(&opening_balance.account) {
+                Some(account) if account.account_type != opening_balance.account_type => {
+                    return Err(LedgerError::ConflictingAccountType {
+                        name: account.name.clone(),
+                        account_type: opening_balance.account_type,
+                        chart_type: account.account_type,
+                    })
+                }
+                Some(_) => {}
+                None => self
+                    .chart
+                    .add(&opening_balance.account, opening_balance.account_type),
+            }
+            let account = self.chart.get(&opening_balance.account).unwrap();
+            let (debit_account, credit_account) = match account.normal_side {
+                EntrySide::Debit => (account.name.as_str(), OPENING_BALANCE_EQUITY),
+                EntrySide::Credit => (OPENING_BALANCE_EQUITY, account.name.as_str()),
+            };
+            let entry = JournalEntry::transfer(
+                date,
+                &format!("Opening balance of {}", account.name),
+                debit_account,
+                credit_account,
+                Money::new(opening_balance.amount, self.currency),
+            );
+            self.post(entry)?;
+        }
+        Ok(())
+    }
+
+    /// Books every extracted transaction between the bank account and its category, or
+    /// the counter account for its kind if it has none.
+    pub fn post_transactions(
+        &mut self,
+        transactions: &ExtractedTransactions,
+        categorizer: &Categorizer,
+    ) -> Result<(), LedgerError> {
+        self.post_transaction_list(
+            TransactionSubtype::Deposit,
+            &transactions.deposits,
+            categorizer,
+        )?;
+        self.post_transaction_list(
+            TransactionSubtype::DebitCardPurchase,
+            &transactions.card_purchases,
+            categorizer,
+        )?;
+        self.post_transaction_list(
+            TransactionSubtype::OnlinePayment,
+            &transactions.payments,
+            categorizer,
+        )?;
+        self.post_transaction_list(
+            TransactionSubtype::TransferOut,
+            &transactions.transfers_out,
+            categorizer,
+        )?;
+        self.post_transaction_list(TransactionSubtype::Fee, &transactions.fees, categorizer)
+    }
+
+    fn post_transaction_list<T: TransactionKindConst>(
+        &mut self,
+        subtype: TransactionSubtype,
+        transactions: &[T],
+        categorizer: &Categorizer,
+    ) -> Result<(), LedgerError> {
+        for transaction in transactions {
+            let source = transaction.source();
+            let description = source.raw_text.split_whitespace().collect::<Vec<&str>>();
+            let mut entry = JournalEntry::transfer(
+                transaction.date(),
+                &description.join(" "),
+                BANK_ACCOUNT,
+                categorizer
+                    .category(subtype, transaction)
+                    .unwrap_or(counter_account(subtype)),
+                transaction.signed_amount(),
+            );
+            entry.source = Some(source.clone());
+            self.post(entry)?;
+        }
+        Ok(())
+    }
+
+    /// The balance of the named account, positive when on its normal side.
+    pub fn balance(&self, name: &str) -> Result<Money, LedgerError> {
+        self.balance_between(name, NaiveDate::MIN, NaiveDate::MAX)
+    }
+
+    /// The net movement of the named account from entries dated `start` through `end`.
+    pub fn balance_between(
+        &self,
+        name: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Money, LedgerError> {
+        let account = self
+            .chart
+            .get(name)
+            .ok_or_else(|| LedgerError::UnknownAccount {
+                name: String::from(name),
+            })?;
+        Ok(self.account_balance_between(account, start, end))
+    }
+
+    /// The net movement of `account` from entries dated `start` through `end`, positive
+    /// when on its normal side.
+    fn account_balance_between(
+        &self,
+        account: &Account,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Money {
+        let mut balance = Money::zero(self.currency);
+        for posting in self
+            .entries
+            .iter()
+            .filter(|entry| (start..=end).contains(&entry.date))
+            .flat_map(|entry| &entry.postings)
+            .filter(|posting| posting.account == account.name)
+        {
+            balance = if posting.side == account.normal_side {
+                Money::new(balance.amount + posting.amount.amount, self.currency)
+            } else {
+                Money::new(balance.amount - posting.amount.amount, self.currency)
+            };
+        }
+        balance
+    }
+
+    /// The sum of the balances of all accounts of `account_type` at the end of `date`.
+    pub fn total_as_of(&self, account_type: AccountType, date: NaiveDate) -> Money {
+        self.total_between(account_type, NaiveDate::MIN, date)
+    }
+
+    /// Every account of `account_type` with its balance, in chart order.
+    pub fn account_balances(&self, account_type: AccountType) -> Vec<(&Account, Money)> {
+        self.account_balances_between(account_type, NaiveDate::MIN, NaiveDate::MAX)
+    }
+
+    /// Every account of `account_type` with its movement from `start` through `end`, in
+    /// chart order.
+    pub fn account_balances_between(
+        &self,
+        account_type: AccountType,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(&Account, Money)> {
+        self.chart
+            .accounts()
+            .iter()
+            .filter(|account| account.account_type == account_type)
+            .map(|account| (account, self.account_balance_between(account, start, end)))
+            .collect()
+    }
+
+    /// The sum of the movements from `start` through `end` of all accounts of
+    /// `account_type`, with contra accounts subtracted.
+    pub fn total_between(
+        &self,
+        account_type: AccountType,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Money {
+        let mut total = Money::zero(self.currency);
+        for (account, balance) in self.account_balances_between(account_type, start, end) {
+            total = if account.normal_side == account_type.normal_side() {
+                Money::new(total.amount + balance.amount, self.currency)
+            } else {
+                Money::new(total.amount - balance.amount, self.currency)
+            };
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::SourceLocation;
+    use crate::profile::ParsingProfile;
+    use crate::provenance::Provenance;
+    use crate::section_utils::StatementSection;
+    use fastnum::decimal::Context;
+    use fastnum::D256;
+    use std::path::PathBuf;
+
+    fn usd(amount: &str) -> Money {
+        Money::new(
+            D256::from_str(amount, Context::default()).unwrap(),
+            Currency::Usd,
+        )
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn source(raw_text: &str, section: StatementSection) -> Provenance {
+        let location = SourceLocation {
+            file: PathBuf::from("statement.pdf"),
+            page: 1,
+            byte_offset: 0,
+        };
+        Provenance {
+            start: location.clone(),
+            end: location,
+            raw_text: String::from(raw_text),
+            section,
+            rule: String::from("test"),
+        }
+    }
+
+    #[test]
+    fn every_transaction_posts_a_balanced_entry_against_the_bank_account() {
+        let profile = ParsingProfile::default();
+        let mut transactions = ExtractedTransactions::default();
+        let mut warnings = vec![];
+        for (subtype, raw_amount, raw_text, section) in [
+            (
+                TransactionSubtype::Deposit,
+                "1,000.00",
+                "01/03 Remote Online Deposit 1,000.00",
+                StatementSection::DepositsAndAdditions,
+            ),
+            (
+                TransactionSubtype::DebitCardPurchase,
+                "120.00",
+                "01/05 Card Purchase Office Depot 120.00",
+                StatementSection::AtmAndDebitCardWithdrawals,
+            ),
+            (
+                TransactionSubtype::DebitCardPurchase,
+                "20.00 CR",
+                "01/06 Card Purchase Return Office Depot 20.00 CR",
+                StatementSection::AtmAndDebitCardWithdrawals,
+            ),
+            (
+                TransactionSubtype::TransferOut,
+                "300.00",
+                "01/10 Online Transfer To Sav 300.00",
+                StatementSection::ElectronicWithdrawals,
+            ),
+            (
+                TransactionSubtype::Fee,
+                "15.00",
+                "01/31 Monthly Service Fee 15.00",
+                StatementSection::Fees,
+            ),
+        ] {
+            transactions.push(
+                subtype,
+                date(3),
+                raw_amount,
+                &profile,
+                source(raw_text, section),
+                &mut warnings,
+            );
+        }
+        assert!(warnings.is_empty());
+
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        ledger.post_opening_balance(date(1), usd("500")).unwrap();
+        ledger
+            .post_transactions(&transactions, &Categorizer::default())
+            .unwrap();
+
+        assert_eq!(ledger.entries.len(), 6);
+        assert_eq!(ledger.balance(BANK_ACCOUNT).unwrap(), usd("1085"));
+        assert_eq!(ledger.balance(CARD_PURCHASES).unwrap(), usd("100"));
+        assert_eq!(ledger.balance(UNCLASSIFIED_TRANSFERS).unwrap(), usd("300"));
+        assert_eq!(ledger.balance(OWNER_DRAWS).unwrap(), usd("0"));
+        assert_eq!(
+            ledger.total_as_of(AccountType::Revenue, date(31)),
+            usd("1000")
+        );
+        assert_eq!(
+            ledger.total_as_of(AccountType::Expense, date(31)),
+            usd("115")
+        );
+        assert_eq!(
+            ledger.total_as_of(AccountType::Equity, date(31)),
+            usd("200")
+        );
+        let refund = &ledger.entries[3];
+        assert_eq!(refund.postings[0].account, BANK_ACCOUNT);
+        assert_eq!(refund.postings[0].side, EntrySide::Debit);
+        assert_eq!(refund.postings[0].amount, usd("20"));
+    }
+
+    #[test]
+    fn rejects_unbalanced_and_unknown_account_entries() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        let mut entry =
+            JournalEntry::transfer(date(2), "Rent", CARD_PURCHASES, BANK_ACCOUNT, usd("10"));
+        entry.postings[1].amount = usd("9");
+        assert!(matches!(
+            ledger.post(entry),
+            Err(LedgerError::Unbalanced { .. })
+        ));
+        let entry = JournalEntry::transfer(date(2), "Rent", "Rent", BANK_ACCOUNT, usd("10"));
+        assert_eq!(
+            ledger.post(entry),
+            Err(LedgerError::UnknownAccount {
+                name: String::from("Rent")
+            })
+        );
+        assert!(ledger.entries.is_empty());
+    }
+
+    #[test]
+    fn opening_balance_must_keep_the_chart_type_of_its_account() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        let opening_balances = parse_opening_balances("Loans Payable,asset,2000.00\n").unwrap();
+        assert_eq!(
+            ledger.post_opening_balances(date(1), &opening_balances),
+            Err(LedgerError::ConflictingAccountType {
+                name: String::from(LOANS_PAYABLE),
+                account_type: AccountType::Asset,
+                chart_type: AccountType::Liability,
+            })
+        );
+        assert!(ledger.entries.is_empty());
+
+        let opening_balances = parse_opening_balances("Loans Payable,liability,2000.00\n").unwrap();
+        ledger
+            .post_opening_balances(date(1), &opening_balances)
+            .unwrap();
+        assert_eq!(ledger.balance(LOANS_PAYABLE), Ok(usd("2000")));
+    }
+
+    #[test]
+    fn opening_balance_of_the_bank_account_is_refused() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        let opening_balances =
+            parse_opening_balances(&format!("{},asset,500.00\n", BANK_ACCOUNT)).unwrap();
+        assert_eq!(
+            ledger.post_opening_balances(date(1), &opening_balances),
+            Err(LedgerError::BankAccountOpeningBalance)
+        );
+        assert!(ledger.entries.is_empty());
+    }
+}
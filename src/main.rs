@@ -1,206 +1,475 @@
+use crate::balance_utils::replay_daily_balances;
+use crate::category_utils::Categorizer;
+use crate::continuity_utils::{continuity_issues, statement_month, ContinuityIssue};
+use crate::errors::ParseError;
+use crate::fx_utils::ExchangeRates;
 use crate::io_utils::glob_files_to_process;
-use crate::parse_utils::{
-    extract_card_purchase_captures_for_re, extract_deposit_captures_for_re,
-    extract_online_payment_captures_for_re, extract_transfers_out_captures_for_re,
-    parse_begin_or_end_bal_amt, parse_statement_begin_or_end_year, DebitCardPurchase, Deposit,
-    OnlinePayment, TransferOut,
+use crate::ledger_utils::{
+    load_opening_balances, Ledger, OpeningBalance, BANK_ACCOUNT, UNCLASSIFIED_TRANSFERS,
 };
-use crate::pyo3_pdf_service::{extract_text_from_page, get_page_count};
-use chrono::Datelike;
-use clap::Parser;
-use fastnum::decimal::{Context, D256};
-use regex::Regex;
+use crate::money_utils::{Currency, Money};
+use crate::parse_utils::{TransactionKindConst, TransactionSubtype};
+use crate::profile::{LocalePreset, ParsingProfile};
+use crate::pyo3_pdf_service::embedded_python;
+use crate::redact_utils::Redactor;
+use crate::report_utils::{
+    BalanceSheet, CashFlowStatement, EquityRollforward, FiscalYear, IncomeStatement, PeriodSummary,
+    ReportColumns,
+};
+use crate::scanner::StatementScanner;
+use crate::statement::{process_statement, PageExtractor, StatementResult};
+use crate::subprocess_pdf_service::PythonCommand;
+use crate::suggest_utils::{load_labels, LabeledTransaction};
+use crate::summary_utils::summary_mismatches;
+use crate::synthetic_utils::{generate_statements, GeneratorSpec};
+use crate::tax_utils::{ScheduleC, TaxLineMap};
+use crate::worker_pool::{default_jobs, map_in_order};
+use chrono::{Datelike, NaiveDate};
+use clap::{Args, Parser, Subcommand};
 use std::error::Error;
-use std::ops::Range;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
+pub mod balance_utils;
+pub mod category_utils;
+pub mod continuity_utils;
+pub mod errors;
+pub mod fx_utils;
 pub mod io_utils;
+pub mod ledger_utils;
+pub mod money_utils;
+pub mod page_utils;
 pub mod parse_utils;
+pub mod pdf_utils;
+pub mod period_utils;
+pub mod profile;
+pub mod provenance;
 pub mod pyo3_pdf_service;
+pub mod redact_utils;
+pub mod report_utils;
+pub mod rng_utils;
+pub mod scanner;
+pub mod section_utils;
+pub mod statement;
+pub mod subprocess_pdf_service;
+pub mod suggest_utils;
+pub mod summary_utils;
+pub mod synthetic_utils;
+pub mod tax_utils;
+pub mod worker_pool;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    process: Option<ProcessArgs>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Pseudonymize extracted statement text into fixtures that can be shared
+    Redact(RedactArgs),
+    /// Generate synthetic statement pdfs with their text and expected parse
+    Generate(GenerateArgs),
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Directory the statements are written to
+    #[arg(long)]
+    out_dir: PathBuf,
+    /// Spec file with `key = value` lines [default: built-in spec]
+    #[arg(long)]
+    spec: Option<PathBuf>,
+    /// Seed to use instead of the spec's
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args)]
+struct RedactArgs {
+    /// Statement text files, as written to the txt directory
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// Directory the redacted files are written to, under their original names
+    #[arg(long)]
+    out_dir: PathBuf,
+    /// Seed for the replacement words, numbers and amount scale
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    /// How the bank prints dates, amounts, headings and labels
+    #[arg(long, value_enum, default_value = "us")]
+    locale: LocalePreset,
+}
+
+/// Reads, parses and reports every statement in a directory.
+#[derive(Args)]
+struct ProcessArgs {
     pdf_dir: String,
-    txt_dir: String,
+    /// Directory the extracted text of each statement is written to
+    txt_dir: PathBuf,
+    /// How the bank prints dates, amounts, headings and labels
+    #[arg(long, value_enum, default_value = "us")]
+    locale: LocalePreset,
+    /// Currency of the account, if not the locale's usual one
+    #[arg(long, value_enum)]
+    currency: Option<Currency>,
+    /// Currency to also report totals in
+    #[arg(long, value_enum, requires = "rates")]
+    report_currency: Option<Currency>,
+    /// Exchange rate file with `date,from,to,rate` lines
+    #[arg(long)]
+    rates: Option<PathBuf>,
+    /// Number of statements to read and parse at once [default: one per core]
+    #[arg(long)]
+    jobs: Option<usize>,
+    /// Python interpreter that extracts page text when more than one job runs [default: the
+    /// embedded one]
+    #[arg(long)]
+    python: Option<String>,
+    /// Date the statements are read as of, in `YYYY-MM-DD` form [default: today]
+    #[arg(long)]
+    as_of: Option<NaiveDate>,
+    /// Rules file that books transactions to categories [default: one account per kind]
+    #[arg(long)]
+    rules: Option<PathBuf>,
+    /// Hand-labeled transactions to suggest categories from, as `category,kind,amount,description` lines
+    #[arg(long)]
+    labels: Option<PathBuf>,
+    /// Confidence from 0 to 1 at which a suggested category is applied
+    #[arg(long, default_value_t = 0.9, requires = "labels")]
+    auto_apply: f64,
+    /// File to write every transaction with its category to, in the labels format
+    #[arg(long)]
+    write_labels: Option<PathBuf>,
+    /// First day of the income statement [default: start of the earliest statement]
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /// Last day of the income statement [default: end of the latest statement]
+    #[arg(long)]
+    to: Option<NaiveDate>,
+    /// Columns of the income statement
+    #[arg(long, value_enum, default_value = "total")]
+    columns: ReportColumns,
+    /// Month the fiscal year starts in, from 1 for January to 12 for December
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=12))]
+    fiscal_year_start: u32,
+    /// File to also write the income statement to, as comma separated values
+    #[arg(long)]
+    income_statement_csv: Option<PathBuf>,
+    /// Date of the balance sheet [default: last day of the income statement]
+    #[arg(long)]
+    balance_sheet_date: Option<NaiveDate>,
+    /// Opening balances of accounts the statements do not show, as `account,type,amount` lines
+    #[arg(long)]
+    opening_balances: Option<PathBuf>,
+    /// Year to total categorized transactions per Schedule C line for
+    #[arg(long)]
+    tax_year: Option<i32>,
+    /// File mapping categories to Schedule C lines, as `category = line` lines
+    #[arg(long, requires = "tax_year")]
+    tax_lines: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match (&cli.command, &cli.process) {
+        (Some(Command::Redact(redact_args)), _) => redact(redact_args),
+        (Some(Command::Generate(generate_args)), _) => generate(generate_args),
+        (None, Some(process_args)) => run(process_args),
+        (None, None) => unreachable!("clap requires the statement arguments without a subcommand"),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
 
-fn main() {
-    let args = Cli::parse();
+fn run(args: &ProcessArgs) -> Result<(), Box<dyn Error>> {
     let input_file_ext = "pdf";
-    let pdf_file_paths: Vec<PathBuf> =
-        glob_files_to_process(&args.pdf_dir, input_file_ext).unwrap();
+    let pdf_file_paths: Vec<PathBuf> = glob_files_to_process(&args.pdf_dir, input_file_ext)?;
     println!("{:?}", pdf_file_paths);
 
-    let pdf_file_path: PathBuf = pdf_file_paths[0].clone();
-    pyo3::prepare_freethreaded_python();
-    let pg_count: u8 = get_page_count(&pdf_file_path).unwrap();
-    println!("Page count: {:?}", pg_count);
-    let page_range: Range<u8> = 1..pg_count + 1;
-    let mut pypdf_reader_page_index: u8;
-    let mut raw_pg_text: Result<String, Box<dyn Error>>;
-    let mut pdf_page_str: String;
-    let mut pdf_page_strs: Vec<String> = Vec::new();
-
-    for page_index in page_range {
-        println!("----Page {}----", page_index);
-        pypdf_reader_page_index = page_index - 1;
-        raw_pg_text = extract_text_from_page(&pdf_file_path, pypdf_reader_page_index);
-        pdf_page_str = match raw_pg_text {
-            Ok(pg_text_str) => pg_text_str,
-            Err(e) => {
-                println!("No text extracted from page {}. {:?}", page_index, e);
-                continue;
-            }
+    let mut profile = ParsingProfile::for_locale(args.locale);
+    if let Some(currency) = args.currency {
+        profile.currency = currency;
+    }
+    let scanner = StatementScanner::new(profile);
+    let mut categorizer = match &args.rules {
+        Some(rules_path) => Categorizer::load(rules_path)?,
+        None => Categorizer::default(),
+    }
+    .with_locale(&scanner.profile.locale);
+    let opening_balances = match &args.opening_balances {
+        Some(opening_balances_path) => load_opening_balances(opening_balances_path)?,
+        None => vec![],
+    };
+    let tax_lines = match &args.tax_lines {
+        Some(tax_lines_path) => TaxLineMap::load(tax_lines_path)?,
+        None => TaxLineMap::default(),
+    };
+    if !(0.0..=1.0).contains(&args.auto_apply) {
+        return Err(format!("--auto-apply {} is not between 0 and 1", args.auto_apply).into());
+    }
+    if let Some(labels_path) = &args.labels {
+        categorizer = categorizer.with_suggestions(load_labels(labels_path)?, args.auto_apply)?;
+    }
+    let report_conversion = match (&args.rates, args.report_currency) {
+        (Some(rates_path), Some(report_currency)) => {
+            Some((ExchangeRates::load(rates_path)?, report_currency))
+        }
+        _ => None,
+    };
+
+    // Python holds one interpreter lock per process, so parallel jobs each extract their
+    // pages in a Python process of their own.
+    let jobs = args.jobs.unwrap_or_else(default_jobs);
+    let extractor = if jobs > 1 {
+        let python = match &args.python {
+            Some(program) => PythonCommand::new(program),
+            None => embedded_python()?,
         };
+        PageExtractor::Subprocess { python }
+    } else {
+        pyo3::prepare_freethreaded_python();
+        PageExtractor::InProcess
+    };
+    let as_of = args
+        .as_of
+        .unwrap_or_else(|| chrono::Local::now().date_naive());
+    let statements = map_in_order(&pdf_file_paths, jobs, |pdf_file_path| {
+        process_statement(pdf_file_path, &extractor, &scanner, as_of, &args.txt_dir)
+    });
 
-        println!("{}", pdf_page_str);
-        pdf_page_strs.push(pdf_page_str);
-    }
-
-    let statement_year_re = Regex::new(r"(?<begin_year>\d{4})\s+through\s").unwrap();
-    let mut statement_year: i32 = 0;
-    let mut one_indexed_page: usize;
-    let begin_balance_re = Regex::new(r"(?m)^Beginning\sBalance.+[$](.+)$").unwrap();
-    let mut begin_bal_usd: Option<fastnum::decimal::Decimal<4>> = None;
-    let end_balance_re = Regex::new(r"(?m)^Ending\sBalance.+[$](.+)$").unwrap();
-    let mut ending_bal_usd: Option<fastnum::decimal::Decimal<4>> = None;
-    let mut page_deposits_vec: Vec<Deposit> = vec![];
-    let mut deposits_vec: Vec<Deposit> = vec![];
-    let mut transaction_month_str: &str;
-    let mut transaction_month: u32;
-    let mut transaction_day_str: &str;
-    let mut transaction_day: u32;
-    let mut transaction_year: i32;
-    let mut transaction_amount: fastnum::decimal::Decimal<4>;
-    let mut page_purchases_vec: Vec<DebitCardPurchase> = vec![];
-    let mut card_purchases_vec: Vec<DebitCardPurchase> = vec![];
-    let mut page_payments_vec: Vec<OnlinePayment> = vec![];
-    let mut payments_vec: Vec<OnlinePayment> = vec![];
-    let mut page_transfers_out_vec: Vec<TransferOut> = vec![];
-    let mut transfers_out_vec: Vec<TransferOut> = vec![];
-
-    let page_str_iter = pdf_page_strs.iter().enumerate();
-
-    for (page_num, page_str) in page_str_iter {
-        one_indexed_page = page_num + 1;
-        println!("Parsing page {:#?}", one_indexed_page);
-        if statement_year == 0 {
-            if let Some(year_capture) = statement_year_re.captures_iter(page_str).next() {
-                statement_year = parse_statement_begin_or_end_year(year_capture);
-            };
-        }
-        if begin_bal_usd.is_none() {
-            if let Some(bal_capture) = begin_balance_re.captures_iter(page_str).next() {
-                begin_bal_usd = Some(parse_begin_or_end_bal_amt(bal_capture));
+    let (processed, failed_count) = check_statements(statements, &categorizer);
+    let labels: Vec<(LabeledTransaction, bool)> = processed
+        .iter()
+        .flat_map(|statement| categorizer.labels(&statement.transactions))
+        .collect();
+    print_continuity_check(&processed);
+    print_reports(
+        &processed,
+        &categorizer,
+        &opening_balances,
+        &tax_lines,
+        report_conversion.as_ref(),
+        args,
+    )?;
+    if let Some(labels_path) = &args.write_labels {
+        // Unconfirmed categories are written commented out, to be checked before the
+        // line is uncommented and trained on.
+        let labels_str: String = labels
+            .iter()
+            .map(|(label, confirmed)| {
+                if *confirmed {
+                    format!("{}\n", label)
+                } else {
+                    format!("# {}\n", label)
+                }
+            })
+            .collect();
+        std::fs::write(labels_path, labels_str)?;
+        let confirmed_count = labels.iter().filter(|(_, confirmed)| *confirmed).count();
+        println!(
+            "{} labels written to {}, {} more commented out to check",
+            confirmed_count,
+            labels_path.display(),
+            labels.len() - confirmed_count
+        );
+    }
+    if failed_count > 0 {
+        return Err(format!(
+            "{} of {} statements not processed",
+            failed_count,
+            pdf_file_paths.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Prints the transactions and checks of each parsed statement, and returns the
+/// statements that could be checked with the number that could not.
+fn check_statements(
+    statements: Vec<Result<StatementResult, ParseError>>,
+    categorizer: &Categorizer,
+) -> (Vec<StatementResult>, usize) {
+    let mut failed_count: usize = 0;
+    let mut processed: Vec<StatementResult> = vec![];
+    for statement in statements {
+        match statement {
+            Ok(statement) => {
+                println!("----{}----", statement.file.display());
+                print_transactions(&statement, categorizer);
+                if let Err(e) = print_statement_checks(&statement, categorizer) {
+                    eprintln!("Error: {}: {}", statement.file.display(), e);
+                    failed_count += 1;
+                    continue;
+                }
+                print_warnings(&statement);
+                processed.push(statement);
             }
-        }
-        if ending_bal_usd.is_none() {
-            if let Some(bal_capture) = end_balance_re.captures_iter(page_str).next() {
-                ending_bal_usd = Some(parse_begin_or_end_bal_amt(bal_capture));
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                failed_count += 1;
             }
         }
+    }
+    (processed, failed_count)
+}
 
-        page_deposits_vec = extract_deposit_captures_for_re(page_str, statement_year);
-        if !page_deposits_vec.is_empty() {
-            for dep in &page_deposits_vec {
-                transaction_month = dep.date.month();
-                transaction_day = dep.date.day();
-                transaction_year = dep.date.year();
-                transaction_amount = dep.amount;
-                println!(
-                    "Page {one_indexed_page} Deposit {transaction_month}/{transaction_day}/{transaction_year} {transaction_amount:.2}",
-                );
+/// Writes a redacted copy of each statement text file into the output directory.
+fn redact(args: &RedactArgs) -> Result<(), Box<dyn Error>> {
+    let mut redactor = Redactor::new(args.seed, ParsingProfile::for_locale(args.locale));
+    std::fs::create_dir_all(&args.out_dir)?;
+    for input_path in &args.inputs {
+        let file_name = input_path
+            .file_name()
+            .ok_or_else(|| format!("{} is not a file", input_path.display()))?;
+        let output_path = args.out_dir.join(file_name);
+        if let (Ok(output), Ok(input)) = (output_path.canonicalize(), input_path.canonicalize()) {
+            if output == input {
+                return Err(format!("{} would overwrite its input", output_path.display()).into());
             }
-            deposits_vec.extend(page_deposits_vec);
         }
+        let statement_text = std::fs::read_to_string(input_path)?;
+        std::fs::write(&output_path, redactor.redact(&statement_text))?;
+        println!("{} -> {}", input_path.display(), output_path.display());
+    }
+    Ok(())
+}
 
-        page_purchases_vec = extract_card_purchase_captures_for_re(page_str, statement_year);
-        if !page_purchases_vec.is_empty() {
-            for purch in &page_purchases_vec {
-                transaction_month = purch.date.month();
-                transaction_day = purch.date.day();
-                transaction_year = purch.date.year();
-                transaction_amount = purch.amount;
-                println!(
-                    "Page {one_indexed_page} Debit Card Purchase {transaction_month}/{transaction_day}/{transaction_year} {transaction_amount:.2}",
-                );
-            }
-            card_purchases_vec.extend(page_purchases_vec);
-        }
+/// Writes the pdf, text and expected parse of every statement the spec describes.
+fn generate(args: &GenerateArgs) -> Result<(), Box<dyn Error>> {
+    let mut spec = match &args.spec {
+        Some(spec_path) => GeneratorSpec::load(spec_path)?,
+        None => GeneratorSpec::default(),
+    };
+    if let Some(seed) = args.seed {
+        spec.seed = seed;
+    }
+    for statement in generate_statements(&spec) {
+        statement.write_files(&args.out_dir)?;
+        println!(
+            "{} {} pages, {} transactions",
+            args.out_dir.join(&statement.name).display(),
+            statement.pdf_page_strs.len(),
+            statement.transactions.len()
+        );
+    }
+    Ok(())
+}
 
-        page_payments_vec = extract_online_payment_captures_for_re(page_str, statement_year);
-        if !page_payments_vec.is_empty() {
-            for payment in &page_payments_vec {
-                transaction_month = payment.date.month();
-                transaction_day = payment.date.day();
-                transaction_year = payment.date.year();
-                transaction_amount = payment.amount;
-                println!(
-                    "Page {one_indexed_page} Online Payment {transaction_month}/{transaction_day}/{transaction_year} {transaction_amount:.2}",
-                );
-            }
-            payments_vec.extend(page_payments_vec);
-        }
+fn print_transactions(statement: &StatementResult, categorizer: &Categorizer) {
+    let transactions = &statement.transactions;
+    print_transaction_list(
+        "Deposit",
+        TransactionSubtype::Deposit,
+        &transactions.deposits,
+        categorizer,
+    );
+    print_transaction_list(
+        "Debit Card Purchase",
+        TransactionSubtype::DebitCardPurchase,
+        &transactions.card_purchases,
+        categorizer,
+    );
+    print_transaction_list(
+        "Online Payment",
+        TransactionSubtype::OnlinePayment,
+        &transactions.payments,
+        categorizer,
+    );
+    print_transaction_list(
+        "Transfer Out",
+        TransactionSubtype::TransferOut,
+        &transactions.transfers_out,
+        categorizer,
+    );
+    print_transaction_list(
+        "Fee",
+        TransactionSubtype::Fee,
+        &transactions.fees,
+        categorizer,
+    );
+}
 
-        page_transfers_out_vec = extract_transfers_out_captures_for_re(page_str, statement_year);
-        if !page_transfers_out_vec.is_empty() {
-            for transfer in &page_transfers_out_vec {
-                transaction_month = transfer.date.month();
-                transaction_day = transfer.date.day();
-                transaction_year = transfer.date.year();
-                transaction_amount = transfer.amount;
-                println!(
-                    "Page {one_indexed_page} Transfer Out {transaction_month}/{transaction_day}/{transaction_year} {transaction_amount:.2}",
-                );
-            }
-            transfers_out_vec.extend(page_transfers_out_vec);
+/// Prints each transaction followed by where it was read from and any suggested category.
+fn print_transaction_list<T: TransactionKindConst>(
+    label: &str,
+    subtype: TransactionSubtype,
+    transactions: &[T],
+    categorizer: &Categorizer,
+) {
+    for transaction in transactions {
+        let date = transaction.date();
+        println!(
+            "Page {} {} {}/{}/{} {:.2}",
+            transaction.source().page(),
+            label,
+            date.month(),
+            date.day(),
+            date.year(),
+            transaction.amount()
+        );
+        println!("    from {}", transaction.source());
+        if let Some(suggestion) = categorizer.suggest(subtype, transaction) {
+            println!(
+                "    suggested {} ({:.0}% confident, {})",
+                suggestion.category,
+                suggestion.confidence * 100.0,
+                if suggestion.applied {
+                    "applied"
+                } else {
+                    "not applied"
+                }
+            );
         }
-
-        // println!("Final deposits_vec {:#?}", deposits_vec);
-        // println!("Final card_purchases_vec {:#?}", card_purchases_vec);
-        // println!("Final payments_vec {:#?}", payments_vec);
     }
-    if statement_year == 0 {
-        panic!("Statement start year not parsed")
+}
+
+/// Checks one statement against itself: its pages, its summary box, its daily balances
+/// and its beginning and ending balances.
+fn print_statement_checks(
+    statement: &StatementResult,
+    categorizer: &Categorizer,
+) -> Result<(), Box<dyn Error>> {
+    let transactions = &statement.transactions;
+    let ledger = statement.ledger(categorizer)?;
+    println!("Statement period {}", statement.period);
+    if !statement.is_complete() {
+        println!("Statement INCOMPLETE, totals may be missing transactions:");
+        for page_issue in &statement.page_issues {
+            println!("  {}", page_issue);
+        }
     }
-    let revenue_usd: fastnum::decimal::Decimal<4> = if !deposits_vec.is_empty() {
-        deposits_vec.iter().map(|invoice| invoice.amount).sum()
-    } else {
-        D256::from_str("0.00", Context::default()).unwrap()
-    };
 
-    let card_purchases_usd: fastnum::decimal::Decimal<4> = card_purchases_vec
-        .iter()
-        .map(|expense| expense.amount)
-        .sum();
-    let online_payments_usd: fastnum::decimal::Decimal<4> =
-        payments_vec.iter().map(|expense| expense.amount).sum();
-    let expenses_usd: fastnum::decimal::Decimal<4> = card_purchases_usd + online_payments_usd;
-    let profit_usd = revenue_usd - expenses_usd;
-    println!("Statement year {:>15}", format!("{:?}", statement_year));
-    println!("Revenue {:>22}", format!("{:.2}", revenue_usd));
-    println!("Expenses {:>21}", format!("{:.2}", expenses_usd));
-    if revenue_usd > expenses_usd {
-        println!("Profit {:>23}", format!("{:.2}", profit_usd));
-    } else {
-        println!("Loss {:>22}", format!("{:.2}", profit_usd));
+    if !statement.summary_totals.is_empty() {
+        let mismatches = summary_mismatches(&statement.summary_totals, transactions)?;
+        if mismatches.is_empty() {
+            println!("Summary box totals match the extracted transactions");
+        }
+        for mismatch in mismatches {
+            println!("Summary mismatch {}", mismatch);
+        }
     }
-    let total_transfers_out: fastnum::decimal::Decimal<4> = if !transfers_out_vec.is_empty() {
-        transfers_out_vec.iter().map(|invoice| invoice.amount).sum()
-    } else {
-        D256::from_str("0.00", Context::default()).unwrap()
-    };
-    if total_transfers_out > D256::from_str("0.00", Context::default()).unwrap() {
-        println!(
-            "Total Owner's Draws {:>10}",
-            format!("{:.2}", total_transfers_out)
-        );
+
+    if let Some(begin_bal) = statement.begin_bal {
+        print_daily_balance_check(statement, begin_bal)?;
     }
 
-    let net_change_in_balance: fastnum::decimal::Decimal<4> =
-        ending_bal_usd.unwrap() - begin_bal_usd.unwrap();
+    let (Some(begin_bal), Some(ending_bal)) = (statement.begin_bal, statement.ending_bal) else {
+        println!("Net change in balance not checked, balances missing");
+        return Ok(());
+    };
+    let net_change_in_balance = ending_bal.checked_sub(begin_bal)?;
     let net_change_in_balance_based_on_transactions =
-        revenue_usd - card_purchases_usd - online_payments_usd - total_transfers_out;
+        ledger.balance(BANK_ACCOUNT)?.checked_sub(begin_bal)?;
 
     if net_change_in_balance != net_change_in_balance_based_on_transactions {
         println!("Inflows and outflows and the profit/loss do not match up");
@@ -211,7 +480,271 @@ fn main() {
         );
         println!(
             "Total mismatch {:.2}",
-            net_change_in_balance - net_change_in_balance_based_on_transactions
+            net_change_in_balance.checked_sub(net_change_in_balance_based_on_transactions)?
+        )
+    }
+    Ok(())
+}
+
+/// Checks that the statements chain into one another, and names the months of any
+/// statements missing in between.
+fn print_continuity_check(statements: &[StatementResult]) {
+    if statements.len() < 2 {
+        return;
+    }
+    println!("--------");
+    let issues = continuity_issues(statements);
+    if issues.is_empty() {
+        println!("Statement periods and balances chain without gaps or overlaps");
+        return;
+    }
+    for issue in &issues {
+        println!("Continuity issue {}", issue);
+    }
+    let missing_months: Vec<String> = issues
+        .iter()
+        .filter_map(|issue| match issue {
+            ContinuityIssue::Gap { missing, .. } => Some(missing),
+            _ => None,
+        })
+        .flatten()
+        .map(statement_month)
+        .collect();
+    if !missing_months.is_empty() {
+        println!("Statements to download: {}", missing_months.join(", "));
+    }
+}
+
+/// Books all processed statements into one ledger, after the beginning balance of the
+/// earliest one and the other opening balances, and prints the income statement over the
+/// requested date range, the balance sheet, the equity rollforward, the cash flow
+/// statement and, for a tax year, the Schedule C line totals.
+///
+/// The ledger has one bank account, so statements of different accounts, by the account
+/// number in their header, are refused rather than combined.
+fn print_reports(
+    statements: &[StatementResult],
+    categorizer: &Categorizer,
+    opening_balances: &[OpeningBalance],
+    tax_lines: &TaxLineMap,
+    report_conversion: Option<&(ExchangeRates, Currency)>,
+    args: &ProcessArgs,
+) -> Result<(), Box<dyn Error>> {
+    let mut account_numbers: Vec<&str> = statements
+        .iter()
+        .filter_map(|statement| statement.account_number.as_deref())
+        .collect();
+    account_numbers.sort();
+    account_numbers.dedup();
+    if account_numbers.len() > 1 {
+        return Err(format!(
+            "statements of accounts {} not combined into one set of books; process the statements of each account separately",
+            account_numbers.join(", ")
         )
+        .into());
+    }
+    let Some(earliest) = statements.iter().min_by_key(|s| s.period.start) else {
+        return Ok(());
+    };
+    let mut ledger = Ledger::new(categorizer.chart_of_accounts(), earliest.currency);
+    ledger.post_opening_balances(earliest.period.start, opening_balances)?;
+    for statement in statements {
+        statement.post_to(&mut ledger, categorizer, std::ptr::eq(statement, earliest))?;
+    }
+    let latest_end = statements.iter().map(|s| s.period.end).max().unwrap();
+    let fiscal_year = FiscalYear {
+        start_month: args.fiscal_year_start,
+    };
+    println!("--------");
+    print_period_summary(
+        &ledger,
+        earliest.period.start,
+        latest_end,
+        fiscal_year,
+        report_conversion,
+    )?;
+
+    let start = args.from.unwrap_or(earliest.period.start);
+    let end = args.to.unwrap_or(latest_end);
+    if start > end {
+        return Err(format!("income statement from {} is after {}", start, end).into());
+    }
+    let income_statement =
+        IncomeStatement::from_ledger(&ledger, start, end, args.columns, fiscal_year);
+    println!("--------");
+    print!("{}", income_statement);
+    if let Some(csv_path) = &args.income_statement_csv {
+        std::fs::write(csv_path, income_statement.to_csv())?;
+        println!("Income statement written to {}", csv_path.display());
+    }
+    println!("--------");
+    let balance_sheet_date = args.balance_sheet_date.unwrap_or(end);
+    print!("{}", BalanceSheet::from_ledger(&ledger, balance_sheet_date));
+    println!("--------");
+    print!(
+        "{}",
+        EquityRollforward::from_ledger(&ledger, start, end, args.columns, fiscal_year)?
+    );
+    println!("--------");
+    let cash_flows = CashFlowStatement::from_ledger(&ledger, start, end)?;
+    print!("{}", cash_flows);
+    print_statement_cash_check(statements, &cash_flows)?;
+    if let Some(tax_year) = args.tax_year {
+        println!("--------");
+        print!("{}", ScheduleC::from_ledger(&ledger, tax_year, tax_lines));
+        print_tax_year_coverage(statements, tax_year);
+    }
+    Ok(())
+}
+
+/// Warns when the statements do not cover the whole tax year, since the line totals then
+/// leave out part of it.
+fn print_tax_year_coverage(statements: &[StatementResult], tax_year: i32) {
+    let year_start = NaiveDate::from_ymd_opt(tax_year, 1, 1).unwrap();
+    let year_end = NaiveDate::from_ymd_opt(tax_year, 12, 31).unwrap();
+    let first = statements.iter().map(|s| s.period.start).min();
+    let last = statements.iter().map(|s| s.period.end).max();
+    if let (Some(first), Some(last)) = (first, last) {
+        if first > year_start || last < year_end {
+            println!(
+                "Statements cover {} through {}, not all of {}",
+                first, last, tax_year
+            );
+        }
+    }
+}
+
+/// Prints the combined totals of all statements by month, quarter and year to date, also
+/// in the report currency if one was asked for, and any transfers out still unclassified.
+fn print_period_summary(
+    ledger: &Ledger,
+    start: NaiveDate,
+    end: NaiveDate,
+    fiscal_year: FiscalYear,
+    report_conversion: Option<&(ExchangeRates, Currency)>,
+) -> Result<(), Box<dyn Error>> {
+    let summary = PeriodSummary::from_ledger(ledger, start, end, fiscal_year)?;
+    print!("{}", summary);
+    if let Some((exchange_rates, report_currency)) = report_conversion {
+        if *report_currency != summary.currency {
+            println!("Converted at the rates of the last day of each period");
+            print!("{}", summary.convert(exchange_rates, *report_currency)?);
+        }
+    }
+    let unclassified_transfers = ledger.balance(UNCLASSIFIED_TRANSFERS)?;
+    if !unclassified_transfers.is_zero() {
+        println!(
+            "Unclassified Transfers {:.2} (add owner's draws rules to classify them)",
+            unclassified_transfers
+        );
+    }
+    Ok(())
+}
+
+/// Compares the net change in cash with the beginning and ending balances printed on the
+/// statements, when statements start and end exactly on the cash flow statement's dates.
+fn print_statement_cash_check(
+    statements: &[StatementResult],
+    cash_flows: &CashFlowStatement,
+) -> Result<(), Box<dyn Error>> {
+    let begin_bal = statements
+        .iter()
+        .find(|s| s.period.start == cash_flows.start)
+        .and_then(|s| s.begin_bal);
+    let ending_bal = statements
+        .iter()
+        .find(|s| s.period.end == cash_flows.end)
+        .and_then(|s| s.ending_bal);
+    let (Some(begin_bal), Some(ending_bal)) = (begin_bal, ending_bal) else {
+        return Ok(());
+    };
+    let statement_net_change = ending_bal.checked_sub(begin_bal)?;
+    if statement_net_change == cash_flows.net_change {
+        println!("Net change in cash matches the statement balances");
+    } else {
+        println!(
+            "Net change in cash per the statement balances {:.2}, differs by {:.2}",
+            statement_net_change,
+            statement_net_change.checked_sub(cash_flows.net_change)?
+        );
+    }
+    Ok(())
+}
+
+/// Reports each day whose ending balance in the daily balance table differs from the
+/// balance replayed from the extracted transactions.
+fn print_daily_balance_check(
+    statement: &StatementResult,
+    begin_bal: Money,
+) -> Result<(), Box<dyn Error>> {
+    if statement.daily_balances.is_empty() {
+        return Ok(());
+    }
+    let divergences = replay_daily_balances(
+        begin_bal,
+        &statement.transactions.signed_amounts(),
+        &statement.daily_balances,
+    )?;
+    if divergences.is_empty() {
+        println!("Daily ending balances match the extracted transactions");
+    }
+    for divergence in divergences {
+        println!("Daily balance mismatch {}", divergence);
+    }
+    Ok(())
+}
+
+fn print_warnings(statement: &StatementResult) {
+    if statement.warnings.is_empty() {
+        return;
+    }
+    println!(
+        "{} warning(s) in {}:",
+        statement.warnings.len(),
+        statement.file.display()
+    );
+    for warning in &statement.warnings {
+        println!("  {}", warning);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statement::parse_statement_pages;
+    use fastnum::decimal::Context;
+    use fastnum::D256;
+    use std::path::Path;
+
+    #[test]
+    fn a_statement_that_fails_its_checks_is_skipped() {
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let as_of = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        let spec = GeneratorSpec::parse("seed = 3\nstatements = 3\n").unwrap();
+        let mut statements: Vec<Result<StatementResult, ParseError>> = generate_statements(&spec)
+            .iter()
+            .map(|statement| {
+                parse_statement_pages(
+                    Path::new(&format!("{}.pdf", statement.name)),
+                    &statement.pdf_page_strs,
+                    &scanner,
+                    as_of,
+                    vec![],
+                )
+            })
+            .collect();
+        // A beginning balance in another currency cannot be posted to the ledger.
+        if let Ok(statement) = &mut statements[1] {
+            statement.begin_bal = Some(Money::new(
+                D256::from_str("1.00", Context::default()).unwrap(),
+                Currency::Eur,
+            ));
+        }
+        let bad_file = statements[1].as_ref().unwrap().file.clone();
+
+        let (processed, failed_count) = check_statements(statements, &Categorizer::default());
+        assert_eq!(failed_count, 1);
+        assert_eq!(processed.len(), 2);
+        assert!(processed.iter().all(|statement| statement.file != bad_file));
     }
 }
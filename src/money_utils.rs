@@ -0,0 +1,403 @@
+use crate::parse_utils::TransactionKind;
+use crate::profile::LocaleSettings;
+use clap::ValueEnum;
+use fastnum::decimal::{Context, Decimal};
+use fastnum::D256;
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub(crate) enum Currency {
+    Usd,
+    Eur,
+    Cad,
+    Gbp,
+}
+
+impl Currency {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Cad => "CAD",
+            Currency::Gbp => "GBP",
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "USD" => Ok(Currency::Usd),
+            "EUR" => Ok(Currency::Eur),
+            "CAD" => Ok(Currency::Cad),
+            "GBP" => Ok(Currency::Gbp),
+            other => Err(format!("unknown currency code {:?}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CurrencyMismatch {
+    pub left: Currency,
+    pub right: Currency,
+}
+
+impl fmt::Display for CurrencyMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot combine {} and {} amounts without conversion",
+            self.left, self.right
+        )
+    }
+}
+
+impl std::error::Error for CurrencyMismatch {}
+
+/// An amount in one currency.
+///
+/// Amounts in different currencies are never added or subtracted; convert one of them
+/// with [`crate::fx_utils::ExchangeRates`] first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Money {
+    pub amount: Decimal<4>,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: Decimal<4>, currency: Currency) -> Self {
+        Self { amount, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(Decimal::<4>::ZERO, currency)
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money, CurrencyMismatch> {
+        if self.currency != other.currency {
+            return Err(CurrencyMismatch {
+                left: self.currency,
+                right: other.currency,
+            });
+        }
+        Ok(Money::new(self.amount + other.amount, self.currency))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, CurrencyMismatch> {
+        self.checked_add(-other)
+    }
+
+    /// Adds up `amounts`, all of which must be in `currency`.
+    pub fn total(
+        currency: Currency,
+        amounts: impl IntoIterator<Item = Money>,
+    ) -> Result<Money, CurrencyMismatch> {
+        amounts
+            .into_iter()
+            .try_fold(Money::zero(currency), Money::checked_add)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::new(-self.amount, self.currency)
+    }
+}
+
+/// Formats the amount, honoring any precision such as `{:.2}`, followed by the currency code.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} {}", precision, self.amount, self.currency),
+            None => write!(f, "{} {}", self.amount, self.currency),
+        }
+    }
+}
+
+/// Regex fragment matching one money token as printed on a statement in `locale`, for
+/// embedding in the extractor regexes.
+///
+/// Accepts an optional currency symbol before or after the number, thousands separators,
+/// a leading or trailing minus, parentheses, and a `CR`/`DR` suffix. Amounts without cents
+/// need a currency symbol or thousands separators, so reference numbers such as
+/// `Trace#: 1234` are not read as amounts.
+pub(crate) fn amount_pattern(locale: &LocaleSettings) -> String {
+    let thousands = regex::escape(&locale.thousands_separator.to_string());
+    let decimal = regex::escape(&locale.decimal_separator.to_string());
+    let currency = locale
+        .currency_symbols
+        .iter()
+        .map(|symbol| regex::escape(symbol))
+        .collect::<Vec<String>>()
+        .join("|");
+    let grouped = format!(r"\d{{1,3}}(?:{thousands}\d{{3}})+");
+    format!(
+        r"(?:[-(]\s?)?(?:(?:{currency})\s?-?(?:{grouped}|\d+)(?:{decimal}\d{{2}})?|(?:{grouped}|\d+){decimal}\d{{2}}|{grouped})(?:\s?(?:{currency}))?\)?-?(?:\s?(?:CR|DR)\b)?"
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MoneyParseError {
+    pub token: String,
+}
+
+impl fmt::Display for MoneyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a valid amount {:?}", self.token)
+    }
+}
+
+impl std::error::Error for MoneyParseError {}
+
+/// A parsed money token.
+#[derive(Debug, PartialEq)]
+pub(crate) struct MoneyToken {
+    /// The printed amount, negative when shown with a minus sign or in parentheses.
+    pub amount: Decimal<4>,
+    /// The direction given by a `CR` or `DR` suffix, if any.
+    pub direction: Option<TransactionKind>,
+}
+
+impl MoneyToken {
+    /// The amount as seen from a transaction listed in `direction`.
+    ///
+    /// A token explicitly marked with the opposite direction, such as a `CR` refund listed
+    /// among withdrawals, reduces the listed transaction kind.
+    pub fn amount_for(&self, direction: TransactionKind) -> Decimal<4> {
+        match &self.direction {
+            Some(token_direction) if *token_direction != direction => -self.amount,
+            _ => self.amount,
+        }
+    }
+
+    /// The amount as it affects a balance: `DR` balances are overdrawn.
+    pub fn balance_amount(&self) -> Decimal<4> {
+        self.amount_for(TransactionKind::Credit)
+    }
+}
+
+/// Parses a money token matched by [`amount_pattern`] for the same `locale`.
+pub(crate) fn parse_money_token(
+    token: &str,
+    locale: &LocaleSettings,
+) -> Result<MoneyToken, MoneyParseError> {
+    let invalid = || MoneyParseError {
+        token: String::from(token),
+    };
+    let mut rest = token.trim();
+
+    let direction = if let Some(stripped) = rest.strip_suffix("CR") {
+        rest = stripped.trim_end();
+        Some(TransactionKind::Credit)
+    } else if let Some(stripped) = rest.strip_suffix("DR") {
+        rest = stripped.trim_end();
+        Some(TransactionKind::Debit)
+    } else {
+        None
+    };
+
+    let mut negative = false;
+    if let Some(stripped) = rest.strip_prefix('(') {
+        rest = stripped
+            .trim_end_matches('-')
+            .strip_suffix(')')
+            .ok_or_else(invalid)?;
+        negative = true;
+    }
+    if let Some(stripped) = rest.strip_prefix('-') {
+        rest = stripped;
+        negative = true;
+    }
+    if let Some(stripped) = rest.strip_suffix('-') {
+        rest = stripped;
+        negative = true;
+    }
+    rest = rest.trim();
+    for symbol in &locale.currency_symbols {
+        if let Some(stripped) = rest.strip_suffix(symbol.as_str()) {
+            rest = stripped.trim_end();
+        }
+        if let Some(stripped) = rest.strip_prefix(symbol.as_str()) {
+            rest = stripped.trim_start();
+            if let Some(stripped) = rest.strip_prefix('-') {
+                rest = stripped;
+                negative = true;
+            }
+        }
+    }
+
+    let digits: String = rest
+        .chars()
+        .filter(|c| *c != locale.thousands_separator)
+        .map(|c| {
+            if c == locale.decimal_separator {
+                '.'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let (whole, fraction) = digits.split_once('.').unwrap_or((&digits, "0"));
+    if whole.is_empty()
+        || !whole.chars().all(|c| c.is_ascii_digit())
+        || !(1..=4).contains(&fraction.len())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let amount = D256::from_str(&digits, Context::default()).map_err(|_| invalid())?;
+    Ok(MoneyToken {
+        amount: if negative { -amount } else { amount },
+        direction,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn dec(s: &str) -> Decimal<4> {
+        D256::from_str(s, Context::default()).unwrap()
+    }
+
+    #[test]
+    fn parses_every_printed_format() {
+        let table: [(&str, &str, Option<TransactionKind>); 17] = [
+            ("1,234.56", "1234.56", None),
+            ("$1,234.56", "1234.56", None),
+            ("$ 1,234.56", "1234.56", None),
+            ("0.07", "0.07", None),
+            ("1234567.89", "1234567.89", None),
+            ("12,345,678.90", "12345678.90", None),
+            ("$1,234", "1234", None),
+            ("$50", "50", None),
+            ("1,234", "1234", None),
+            ("-1,234.56", "-1234.56", None),
+            ("-$1,234.56", "-1234.56", None),
+            ("$-1,234.56", "-1234.56", None),
+            ("1,234.56-", "-1234.56", None),
+            ("(1,234.56)", "-1234.56", None),
+            ("($1,234.56)", "-1234.56", None),
+            ("1,234.56 CR", "1234.56", Some(TransactionKind::Credit)),
+            ("1,234.56DR", "1234.56", Some(TransactionKind::Debit)),
+        ];
+        for (token, expected, direction) in table {
+            assert_eq!(
+                parse_money_token(token, &LocaleSettings::us()),
+                Ok(MoneyToken {
+                    amount: dec(expected),
+                    direction
+                }),
+                "token {:?}",
+                token
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        for token in ["", "$", "abc", "1.2.3", "(1,234.56", "12a.00"] {
+            assert!(
+                parse_money_token(token, &LocaleSettings::us()).is_err(),
+                "token {:?}",
+                token
+            );
+        }
+    }
+
+    #[test]
+    fn pattern_matches_whole_token_at_line_end() {
+        let amount_re = Regex::new(&format!(
+            r"(?m)\s(?<amount>{})$",
+            amount_pattern(&LocaleSettings::us())
+        ))
+        .unwrap();
+        let table = [
+            ("Online Transfer To Chk Transaction#: 1 200.00", "200.00"),
+            ("Card Purchase 1,234.56-", "1,234.56-"),
+            ("Returned Item (45.00)", "(45.00)"),
+            ("Interest Adjustment 3.10 CR", "3.10 CR"),
+            ("Wire Fee $25", "$25"),
+            ("Payroll 12,000", "12,000"),
+        ];
+        for (line, expected) in table {
+            let captures = amount_re.captures(line).expect(line);
+            assert_eq!(&captures["amount"], expected, "line {:?}", line);
+        }
+        assert!(amount_re.captures("Trace#: 1234").is_none());
+    }
+
+    #[test]
+    fn suffix_direction_flips_amount_against_listed_direction() {
+        let refund = parse_money_token("12.00 CR", &LocaleSettings::us()).unwrap();
+        assert_eq!(refund.amount_for(TransactionKind::Debit), dec("-12.00"));
+        assert_eq!(refund.amount_for(TransactionKind::Credit), dec("12.00"));
+        let overdrawn = parse_money_token("40.00 DR", &LocaleSettings::us()).unwrap();
+        assert_eq!(overdrawn.balance_amount(), dec("-40.00"));
+    }
+
+    #[test]
+    fn money_refuses_arithmetic_across_currencies() {
+        let usd = Money::new(dec("10.00"), Currency::Usd);
+        let eur = Money::new(dec("4.00"), Currency::Eur);
+        assert_eq!(
+            usd.checked_add(Money::new(dec("2.50"), Currency::Usd)),
+            Ok(Money::new(dec("12.50"), Currency::Usd))
+        );
+        assert_eq!(
+            usd.checked_sub(eur),
+            Err(CurrencyMismatch {
+                left: Currency::Usd,
+                right: Currency::Eur
+            })
+        );
+        assert!(Money::total(Currency::Usd, [usd, eur]).is_err());
+        assert_eq!(format!("{:.2}", -usd), "-10.00 USD");
+    }
+
+    #[test]
+    fn parses_european_formats() {
+        let locale = LocaleSettings::european();
+        let table = [
+            ("1.234,56", "1234.56"),
+            ("1.234,56 €", "1234.56"),
+            ("€1.234,56", "1234.56"),
+            ("-1.234,56 €", "-1234.56"),
+            ("(12,00)", "-12.00"),
+            ("0,07", "0.07"),
+            ("1.234 €", "1234"),
+            ("12.345.678,90 EUR", "12345678.90"),
+        ];
+        for (token, expected) in table {
+            assert_eq!(
+                parse_money_token(token, &locale).map(|t| t.amount),
+                Ok(dec(expected)),
+                "token {:?}",
+                token
+            );
+        }
+        let amount_re =
+            Regex::new(&format!(r"(?m)\s(?<amount>{})$", amount_pattern(&locale))).unwrap();
+        let captures = amount_re
+            .captures("31.01. Miete Januar 1.234,56 €")
+            .unwrap();
+        assert_eq!(&captures["amount"], "1.234,56 €");
+    }
+}
@@ -0,0 +1,421 @@
+use crate::errors::SourceLocation;
+use crate::profile::{labels_pattern, LocaleSettings};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// How many lines at the top and bottom of a page are checked for running headers and footers.
+const HEADER_FOOTER_LINES: usize = 6;
+
+/// Where a stitched line came from.
+#[derive(Debug)]
+struct LineSegment {
+    stitched_start: usize,
+    page: usize,
+    page_byte_offset: usize,
+}
+
+/// The pages of one statement joined into a single text, with running headers and footers
+/// removed, so a transaction that wraps across a page break reads as one entry.
+#[derive(Debug)]
+pub(crate) struct StitchedStatement {
+    pub file: PathBuf,
+    pub text: String,
+    segments: Vec<LineSegment>,
+}
+
+impl StitchedStatement {
+    /// Maps a byte offset in the stitched text back to the page it was read from.
+    pub fn location(&self, stitched_offset: usize) -> SourceLocation {
+        let segment_index = self
+            .segments
+            .partition_point(|segment| segment.stitched_start <= stitched_offset)
+            .saturating_sub(1);
+        match self.segments.get(segment_index) {
+            Some(segment) => SourceLocation {
+                file: self.file.clone(),
+                page: segment.page,
+                byte_offset: segment.page_byte_offset + (stitched_offset - segment.stitched_start),
+            },
+            None => SourceLocation {
+                file: self.file.clone(),
+                page: 1,
+                byte_offset: stitched_offset,
+            },
+        }
+    }
+
+    /// Maps a non-empty byte range of the stitched text back to its start and exclusive
+    /// end on the pages it was read from.
+    pub fn span(&self, stitched_range: Range<usize>) -> (SourceLocation, SourceLocation) {
+        let start = self.location(stitched_range.start);
+        let mut end = self.location(stitched_range.end.max(stitched_range.start + 1) - 1);
+        end.byte_offset += 1;
+        (start, end)
+    }
+}
+
+/// A line with digits masked, so `Page 1 of 3` and `Page 2 of 3` compare equal.
+fn normalize_line(line: &str) -> String {
+    line.trim()
+        .chars()
+        .map(|c| if c.is_ascii_digit() { '#' } else { c })
+        .collect()
+}
+
+/// Whether a normalized line ends with an amount, like the last line of a transaction
+/// that wraps. Such a line is never a running header or footer, even if a transaction
+/// line of the same shape happens to sit at the edge of every page.
+fn ends_with_amount(normalized_line: &str) -> bool {
+    let trimmed = normalized_line.trim_end_matches(|c: char| c != '#');
+    trimmed.ends_with("#.##") || trimmed.ends_with("#,##")
+}
+
+/// The lines near the top or bottom of a page that repeat on every page.
+///
+/// Pages without text, such as pages pypdf could not read, are left out of the comparison.
+fn running_header_footer_lines(pdf_page_strs: &[String]) -> Vec<String> {
+    let text_pages: Vec<&String> = pdf_page_strs
+        .iter()
+        .filter(|page_str| !page_str.trim().is_empty())
+        .collect();
+    if text_pages.len() < 2 {
+        return vec![];
+    }
+    let mut page_counts: HashMap<String, usize> = HashMap::new();
+    for page_str in &text_pages {
+        let lines: Vec<&str> = page_str.lines().collect();
+        let footer_start = lines.len().saturating_sub(HEADER_FOOTER_LINES);
+        let mut edge_lines: Vec<String> = lines
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i < HEADER_FOOTER_LINES || *i >= footer_start)
+            .map(|(_, line)| normalize_line(line))
+            .filter(|line| {
+                !line.is_empty() && !line.starts_with("##/##") && !ends_with_amount(line)
+            })
+            .collect();
+        edge_lines.sort();
+        edge_lines.dedup();
+        for line in edge_lines {
+            *page_counts.entry(line).or_insert(0) += 1;
+        }
+    }
+    page_counts
+        .into_iter()
+        .filter(|(_, count)| *count == text_pages.len())
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Lines that are dropped wherever they appear on a page.
+pub(crate) struct PageMarkers {
+    page_marker_re: Regex,
+    continued_heading_re: Regex,
+    column_heading_re: Regex,
+}
+
+impl PageMarkers {
+    pub fn new(locale: &LocaleSettings) -> Self {
+        let labels = &locale.labels;
+        let (page, of) = &labels.page_of;
+        Self {
+            page_marker_re: Regex::new(&format!(
+                r"(?i)^\s*{}\s+(?<page_number>\d+)\s+{}\s+(?<page_total>\d+)\s*$",
+                regex::escape(page),
+                regex::escape(of)
+            ))
+            .unwrap(),
+            continued_heading_re: Regex::new(&format!(
+                r"^\s*[\p{{Lu}}&\s]+\({}\)\s*$",
+                regex::escape(&labels.continued)
+            ))
+            .unwrap(),
+            column_heading_re: Regex::new(&format!(
+                r"^\s*{}\s*$",
+                labels_pattern(&[labels.column_headings.join(" ")])
+            ))
+            .unwrap(),
+        }
+    }
+
+    /// The `X` and `Y` of the last `Page X of Y` line on a page.
+    fn page_marker(&self, page_str: &str) -> Option<(usize, usize)> {
+        page_str.lines().rev().find_map(|line| {
+            let marker_captures = self.page_marker_re.captures(line)?;
+            let page_number = marker_captures["page_number"].parse().ok()?;
+            let page_total = marker_captures["page_total"].parse().ok()?;
+            Some((page_number, page_total))
+        })
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        self.page_marker_re.is_match(line)
+            || self.continued_heading_re.is_match(line)
+            || self.column_heading_re.is_match(line)
+    }
+}
+
+/// Joins the pages of a statement into one text.
+///
+/// Running headers and footers, `Page X of Y` markers, repeated column headings and
+/// `(continued)` section headings are dropped, so the text of a transaction continues
+/// directly from the bottom of one page into the next.
+pub(crate) fn stitch_pages(
+    pdf_file_path: &Path,
+    pdf_page_strs: &[String],
+    page_markers: &PageMarkers,
+) -> StitchedStatement {
+    let running_lines = running_header_footer_lines(pdf_page_strs);
+    let mut text = String::new();
+    let mut segments: Vec<LineSegment> = vec![];
+
+    for (page_num, page_str) in pdf_page_strs.iter().enumerate() {
+        let lines: Vec<&str> = page_str.lines().collect();
+        let footer_start = lines.len().saturating_sub(HEADER_FOOTER_LINES);
+        let mut page_byte_offset: usize = 0;
+        for (line_index, line) in page_str.split_inclusive('\n').enumerate() {
+            let line_offset = page_byte_offset;
+            page_byte_offset += line.len();
+            let at_page_edge = line_index < HEADER_FOOTER_LINES || line_index >= footer_start;
+            let content = line.trim_end_matches(['\r', '\n']);
+            if (at_page_edge && running_lines.contains(&normalize_line(content)))
+                || page_markers.is_match(content)
+            {
+                continue;
+            }
+            segments.push(LineSegment {
+                stitched_start: text.len(),
+                page: page_num + 1,
+                page_byte_offset: line_offset,
+            });
+            text.push_str(content);
+            text.push('\n');
+        }
+    }
+    StitchedStatement {
+        file: pdf_file_path.to_path_buf(),
+        text,
+        segments,
+    }
+}
+
+/// A reason the pages of a statement may not all have been read.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PageIssue {
+    /// pypdf could not extract the page's text.
+    Unreadable { page: usize },
+    /// The page has no `Page X of Y` footer although other pages do.
+    MissingMarker { page: usize },
+    /// No page is numbered `number` in the footers.
+    MissingNumber { number: usize },
+    /// More than one page is numbered `number`.
+    DuplicatedNumber { number: usize, pages: Vec<usize> },
+    /// The page is numbered lower than the page before it.
+    OutOfOrder {
+        page: usize,
+        number: usize,
+        previous_number: usize,
+    },
+    /// The footers give a page total other than the number of pages in the file.
+    PageTotalMismatch { stated: usize, page_count: usize },
+}
+
+impl fmt::Display for PageIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageIssue::Unreadable { page } => write!(f, "page {} could not be read", page),
+            PageIssue::MissingMarker { page } => {
+                write!(f, "page {} has no \"Page X of Y\" footer", page)
+            }
+            PageIssue::MissingNumber { number } => {
+                write!(f, "no page is numbered {}", number)
+            }
+            PageIssue::DuplicatedNumber { number, pages } => {
+                write!(f, "pages {:?} are all numbered {}", pages, number)
+            }
+            PageIssue::OutOfOrder {
+                page,
+                number,
+                previous_number,
+            } => write!(
+                f,
+                "page {} is numbered {}, after page number {}",
+                page, number, previous_number
+            ),
+            PageIssue::PageTotalMismatch { stated, page_count } => write!(
+                f,
+                "footers give {} pages, the file has {}",
+                stated, page_count
+            ),
+        }
+    }
+}
+
+/// Compares the `Page X of Y` footers with the pages in the file to find pages that are
+/// unreadable, missing, duplicated or out of order.
+///
+/// Statements without any footer numbering are only checked for unreadable pages.
+pub(crate) fn check_page_completeness(
+    pdf_page_strs: &[String],
+    unreadable_pages: &[usize],
+    page_markers: &PageMarkers,
+) -> Vec<PageIssue> {
+    let mut page_issues: Vec<PageIssue> = unreadable_pages
+        .iter()
+        .map(|page| PageIssue::Unreadable { page: *page })
+        .collect();
+    let markers: Vec<(usize, Option<(usize, usize)>)> = pdf_page_strs
+        .iter()
+        .enumerate()
+        .map(|(page_index, page_str)| (page_index + 1, page_markers.page_marker(page_str)))
+        .filter(|(page, _)| !unreadable_pages.contains(page))
+        .collect();
+    if markers.iter().all(|(_, marker)| marker.is_none()) {
+        return page_issues;
+    }
+
+    let mut pages_by_number: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    let mut stated_totals: Vec<usize> = vec![];
+    let mut previous_number: Option<usize> = None;
+    for (page, marker) in markers {
+        let Some((number, total)) = marker else {
+            page_issues.push(PageIssue::MissingMarker { page });
+            continue;
+        };
+        if let Some(previous_number) = previous_number.filter(|previous| number < *previous) {
+            page_issues.push(PageIssue::OutOfOrder {
+                page,
+                number,
+                previous_number,
+            });
+        }
+        previous_number = Some(number);
+        pages_by_number.entry(number).or_default().push(page);
+        if !stated_totals.contains(&total) {
+            stated_totals.push(total);
+        }
+    }
+
+    for (number, pages) in &pages_by_number {
+        if pages.len() > 1 {
+            page_issues.push(PageIssue::DuplicatedNumber {
+                number: *number,
+                pages: pages.clone(),
+            });
+        }
+    }
+    let page_count = pdf_page_strs.len();
+    for stated in &stated_totals {
+        if *stated != page_count {
+            page_issues.push(PageIssue::PageTotalMismatch {
+                stated: *stated,
+                page_count,
+            });
+        }
+    }
+    let expected_total = stated_totals
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(page_count)
+        .max(page_count);
+    for number in 1..=expected_total {
+        if !pages_by_number.contains_key(&number) {
+            page_issues.push(PageIssue::MissingNumber { number });
+        }
+    }
+    page_issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(footers: &[&str]) -> Vec<String> {
+        footers
+            .iter()
+            .map(|footer| format!("ACME LLC\n01/05 Deposit 10.00\n{}\n", footer))
+            .collect()
+    }
+
+    fn markers() -> PageMarkers {
+        PageMarkers::new(&LocaleSettings::us())
+    }
+
+    #[test]
+    fn wrapped_transaction_at_page_edge_is_not_a_running_footer() {
+        let pdf_page_strs: Vec<String> = ["125.00", "750.10"]
+            .iter()
+            .map(|amount| {
+                format!(
+                    "ACME LLC\n01/05 Online Transfer to Sav\nRef 1234 {}\n",
+                    amount
+                )
+            })
+            .collect();
+        assert_eq!(
+            running_header_footer_lines(&pdf_page_strs),
+            vec!["ACME LLC"]
+        );
+    }
+
+    #[test]
+    fn complete_statement_has_no_issues() {
+        let pdf_page_strs = pages(&["Page 1 of 3", "Page 2 of 3", "Page 3 of 3"]);
+        assert!(check_page_completeness(&pdf_page_strs, &[], &markers()).is_empty());
+        let unnumbered = pages(&["", ""]);
+        assert!(check_page_completeness(&unnumbered, &[], &markers()).is_empty());
+    }
+
+    #[test]
+    fn finds_unreadable_missing_duplicated_and_out_of_order_pages() {
+        let mut pdf_page_strs = pages(&["Page 1 of 4", "", "Page 3 of 4", "Page 3 of 4"]);
+        pdf_page_strs[1] = String::new();
+        assert_eq!(
+            check_page_completeness(&pdf_page_strs, &[2], &markers()),
+            vec![
+                PageIssue::Unreadable { page: 2 },
+                PageIssue::DuplicatedNumber {
+                    number: 3,
+                    pages: vec![3, 4]
+                },
+                PageIssue::MissingNumber { number: 2 },
+                PageIssue::MissingNumber { number: 4 },
+            ]
+        );
+
+        let shuffled = pages(&["Page 2 of 3", "Page 1 of 3", "Page 3 of 3"]);
+        assert_eq!(
+            check_page_completeness(&shuffled, &[], &markers()),
+            vec![PageIssue::OutOfOrder {
+                page: 2,
+                number: 1,
+                previous_number: 2
+            }]
+        );
+
+        let truncated = pages(&["Page 1 of 3", "Page 2 of 3"]);
+        assert_eq!(
+            check_page_completeness(&truncated, &[], &markers()),
+            vec![
+                PageIssue::PageTotalMismatch {
+                    stated: 3,
+                    page_count: 2
+                },
+                PageIssue::MissingNumber { number: 3 },
+            ]
+        );
+
+        let unnumbered_page = pages(&["Page 1 of 2", ""]);
+        assert_eq!(
+            check_page_completeness(&unnumbered_page, &[], &markers()),
+            vec![
+                PageIssue::MissingMarker { page: 2 },
+                PageIssue::MissingNumber { number: 2 },
+            ]
+        );
+    }
+}
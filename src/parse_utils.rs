@@ -1,389 +1,514 @@
-use chrono::{Datelike, NaiveDate};
-use fastnum::{decimal::*, D256};
+use crate::errors::{ParseError, SourceLocation};
+use crate::money_utils::{
+    amount_pattern, parse_money_token, Currency, CurrencyMismatch, Money, MoneyParseError,
+};
+use crate::period_utils::StatementPeriod;
+use crate::profile::{LocaleSettings, ParsingProfile};
+use crate::provenance::Provenance;
+use crate::section_utils::StatementSection;
+use chrono::NaiveDate;
 use regex::{Captures, Regex};
+use std::path::Path;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TransactionKind {
     Debit,
     Credit,
 }
 
-trait TransactionKindConst {
+pub(crate) trait TransactionKindConst: Sized {
     const DEBIT_OR_CREDIT: TransactionKind;
-}
 
-struct TransactionProps {
-    pub date: String,
-    pub amount: fastnum::decimal::Decimal<4>,
+    fn from_parts(date: NaiveDate, amount: Money, source: Provenance) -> Self;
+
+    fn date(&self) -> NaiveDate;
+
+    fn amount(&self) -> Money;
+
+    fn source(&self) -> &Provenance;
+
+    /// The amount as it affects the account balance: positive for credits, negative for debits.
+    fn signed_amount(&self) -> Money {
+        match Self::DEBIT_OR_CREDIT {
+            TransactionKind::Credit => self.amount(),
+            TransactionKind::Debit => -self.amount(),
+        }
+    }
+
+    fn new(
+        date: NaiveDate,
+        raw_amount: &str,
+        profile: &ParsingProfile,
+        source: Provenance,
+    ) -> Result<Self, MoneyParseError> {
+        let amount =
+            parse_money_token(raw_amount, &profile.locale)?.amount_for(Self::DEBIT_OR_CREDIT);
+        Ok(Self::from_parts(
+            date,
+            Money::new(amount, profile.currency),
+            source,
+        ))
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct Deposit {
     pub date: NaiveDate,
-    pub amount: fastnum::decimal::Decimal<4>,
+    pub amount: Money,
+    pub source: Provenance,
 }
 
 impl TransactionKindConst for Deposit {
     const DEBIT_OR_CREDIT: TransactionKind = TransactionKind::Credit;
-}
 
-impl Deposit {
-    pub fn new(date: NaiveDate, raw_amount: &mut String) -> Self {
-        raw_amount.retain(|c| c != ',');
+    fn from_parts(date: NaiveDate, amount: Money, source: Provenance) -> Self {
         Self {
             date,
-            amount: D256::from_str(raw_amount, Context::default()).unwrap(),
+            amount,
+            source,
         }
     }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Money {
+        self.amount
+    }
+
+    fn source(&self) -> &Provenance {
+        &self.source
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct DebitCardPurchase {
     pub date: NaiveDate,
-    pub amount: fastnum::decimal::Decimal<4>,
+    pub amount: Money,
+    pub source: Provenance,
 }
 
 impl TransactionKindConst for DebitCardPurchase {
     const DEBIT_OR_CREDIT: TransactionKind = TransactionKind::Debit;
-}
 
-impl DebitCardPurchase {
-    pub fn new(date: NaiveDate, raw_amount: &mut String) -> Self {
-        raw_amount.retain(|c| c != ',');
+    fn from_parts(date: NaiveDate, amount: Money, source: Provenance) -> Self {
         Self {
             date,
-            amount: D256::from_str(raw_amount, Context::default()).unwrap(),
+            amount,
+            source,
         }
     }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Money {
+        self.amount
+    }
+
+    fn source(&self) -> &Provenance {
+        &self.source
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct OnlinePayment {
     pub date: NaiveDate,
-    pub amount: fastnum::decimal::Decimal<4>,
+    pub amount: Money,
+    pub source: Provenance,
 }
 
 impl TransactionKindConst for OnlinePayment {
     const DEBIT_OR_CREDIT: TransactionKind = TransactionKind::Debit;
-}
 
-impl OnlinePayment {
-    pub fn new(date: NaiveDate, raw_amount: &mut String) -> Self {
-        raw_amount.retain(|c| c != ',');
+    fn from_parts(date: NaiveDate, amount: Money, source: Provenance) -> Self {
         Self {
             date,
-            amount: D256::from_str(raw_amount, Context::default()).unwrap(),
+            amount,
+            source,
         }
     }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Money {
+        self.amount
+    }
+
+    fn source(&self) -> &Provenance {
+        &self.source
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct TransferOut {
     pub date: NaiveDate,
-    pub amount: fastnum::decimal::Decimal<4>,
+    pub amount: Money,
+    pub source: Provenance,
 }
 
 impl TransactionKindConst for TransferOut {
     const DEBIT_OR_CREDIT: TransactionKind = TransactionKind::Debit;
+
+    fn from_parts(date: NaiveDate, amount: Money, source: Provenance) -> Self {
+        Self {
+            date,
+            amount,
+            source,
+        }
+    }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Money {
+        self.amount
+    }
+
+    fn source(&self) -> &Provenance {
+        &self.source
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Fee {
+    pub date: NaiveDate,
+    pub amount: Money,
+    pub source: Provenance,
 }
 
-impl TransferOut {
-    pub fn new(date: NaiveDate, raw_amount: &mut String) -> Self {
-        raw_amount.retain(|c| c != ',');
+impl TransactionKindConst for Fee {
+    const DEBIT_OR_CREDIT: TransactionKind = TransactionKind::Debit;
+
+    fn from_parts(date: NaiveDate, amount: Money, source: Provenance) -> Self {
         Self {
             date,
-            amount: D256::from_str(raw_amount, Context::default()).unwrap(),
+            amount,
+            source,
         }
     }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Money {
+        self.amount
+    }
+
+    fn source(&self) -> &Provenance {
+        &self.source
+    }
 }
 
-pub(crate) fn parse_statement_begin_or_end_year(year_capture: Captures) -> i32 {
-    let year_capture_match = year_capture.get(1);
-    let mut year_str = String::from(year_capture_match.unwrap().as_str());
-    year_str.parse::<i32>().expect("Not parsed")
+/// The transaction structs an entry under a section heading can become.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TransactionSubtype {
+    Deposit,
+    DebitCardPurchase,
+    OnlinePayment,
+    TransferOut,
+    Fee,
 }
 
-pub(crate) fn parse_begin_or_end_bal_amt(bal_captures: Captures) -> Decimal<4> {
-    let bal_capture_match = bal_captures.get(1);
-    let mut bal_str = String::from(bal_capture_match.unwrap().as_str());
-    bal_str.retain(|c| c != ',');
-    D256::from_str(&bal_str, Context::default()).unwrap()
+/// Reads the subtype names used in rules files, such as `DebitCardPurchase`.
+impl std::str::FromStr for TransactionSubtype {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "Deposit" => Ok(TransactionSubtype::Deposit),
+            "DebitCardPurchase" => Ok(TransactionSubtype::DebitCardPurchase),
+            "OnlinePayment" => Ok(TransactionSubtype::OnlinePayment),
+            "TransferOut" => Ok(TransactionSubtype::TransferOut),
+            "Fee" => Ok(TransactionSubtype::Fee),
+            other => Err(format!("unknown transaction kind {:?}", other)),
+        }
+    }
 }
 
-pub(crate) fn extract_deposit_captures_for_re(page_str: &str, statement_year: i32) -> Vec<Deposit> {
-    let wire_payment_re =
-    Regex::new(r"(?ms)(?<date>\d{2}\/\d{2})\s(Orig\sCO\sName.+?)(Descr:Payments)(.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d)$")
-        .unwrap();
-    let re_expr: Regex = wire_payment_re;
-    let mut start_byte_offset: usize = 0;
-    let mut trans_byte_offset_opt: Option<usize>;
-    let mut end_byte_offset: usize;
-    let mut match_slice: &str;
-    let mut captures: Captures;
-    let mut raw_amount: String;
-    let mut transaction_month_day_str: String;
-    let mut transaction_month_str: &str;
-    let mut transaction_month: u32;
-    let mut transaction_day_str: &str;
-    let mut transaction_day: u32;
-    let current_date = chrono::Utc::now();
-    let current_year = current_date.year();
-    let mut transaction_year: i32;
-    let mut transaction_date: NaiveDate;
-    let mut deposit: Deposit;
-    let mut deposits: Vec<Deposit> = vec![];
-
-    trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
-    while trans_byte_offset_opt.is_some() {
-        end_byte_offset = trans_byte_offset_opt.unwrap();
-        match_slice = &page_str[start_byte_offset..end_byte_offset];
-        captures = re_expr
-            .captures(match_slice)
-            .expect(".is_some() must not be true");
-        raw_amount = String::from(
-            captures
-                .name("amount_with_commas")
-                .expect("already captured")
-                .as_str(),
-        );
-        transaction_month_day_str =
-            String::from(captures.name("date").expect("already_captured").as_str());
-        let Some((transaction_month_str, transaction_day_str)) =
-            transaction_month_day_str.split_once("/")
-        else {
-            panic!(
-                "transaction_month_day_str {} not parsed.",
-                transaction_month_day_str
-            );
-        };
-        transaction_month = transaction_month_str
-            .parse::<u32>()
-            .expect("Not valid month string");
-        transaction_day = transaction_day_str
-            .parse::<u32>()
-            .expect("Not valid day string");
-        if statement_year != 0 {
-            transaction_year = statement_year;
-        } else {
-            transaction_year = current_year;
+impl TransactionSubtype {
+    pub const ALL: [TransactionSubtype; 5] = [
+        TransactionSubtype::Deposit,
+        TransactionSubtype::DebitCardPurchase,
+        TransactionSubtype::OnlinePayment,
+        TransactionSubtype::TransferOut,
+        TransactionSubtype::Fee,
+    ];
+
+    pub fn debit_or_credit(&self) -> TransactionKind {
+        match self {
+            TransactionSubtype::Deposit => Deposit::DEBIT_OR_CREDIT,
+            TransactionSubtype::DebitCardPurchase => DebitCardPurchase::DEBIT_OR_CREDIT,
+            TransactionSubtype::OnlinePayment => OnlinePayment::DEBIT_OR_CREDIT,
+            TransactionSubtype::TransferOut => TransferOut::DEBIT_OR_CREDIT,
+            TransactionSubtype::Fee => Fee::DEBIT_OR_CREDIT,
+        }
+    }
+
+    /// The subtype of an entry under `section` that no keyword rule refines.
+    pub fn section_default(section: StatementSection) -> Option<Self> {
+        match section {
+            StatementSection::DepositsAndAdditions => Some(TransactionSubtype::Deposit),
+            StatementSection::AtmAndDebitCardWithdrawals => {
+                Some(TransactionSubtype::DebitCardPurchase)
+            }
+            StatementSection::ElectronicWithdrawals => Some(TransactionSubtype::OnlinePayment),
+            StatementSection::Fees => Some(TransactionSubtype::Fee),
+            StatementSection::Summary | StatementSection::DailyEndingBalance => None,
         }
-        transaction_date =
-            NaiveDate::from_ymd_opt(transaction_year, transaction_month, transaction_day)
-                .expect("from_ymd_opt() failed.");
-        deposit = Deposit::new(transaction_date, &mut raw_amount);
-        deposits.push(deposit);
-        start_byte_offset = end_byte_offset + 1;
-        trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
     }
-    deposits
 }
 
-pub(crate) fn extract_card_purchase_captures_for_re(
-    page_str: &str,
-    statement_year: i32,
-) -> Vec<DebitCardPurchase> {
-    let card_purchase_re =
-    Regex::new(r"(?ms)(?<date>\d{2}\/\d{2})\s(Recurring\sCard\sPurchase.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d)$")
-        .unwrap();
-    let re_expr: Regex = card_purchase_re;
-    let mut start_byte_offset: usize = 0;
-    let mut trans_byte_offset_opt: Option<usize>;
-    let mut end_byte_offset: usize;
-    let mut match_slice: &str;
-    let mut captures: Captures;
-    let mut raw_amount: String;
-    let mut transaction_month_day_str: String;
-    let mut transaction_month_str: &str;
-    let mut transaction_month: u32;
-    let mut transaction_day_str: &str;
-    let mut transaction_day: u32;
-    let current_date = chrono::Utc::now();
-    let current_year = current_date.year();
-    let mut transaction_year: i32;
-    let mut transaction_date: NaiveDate;
-    let mut purchase: DebitCardPurchase;
-    let mut purchases: Vec<DebitCardPurchase> = vec![];
-    trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
-    while trans_byte_offset_opt.is_some() {
-        end_byte_offset = trans_byte_offset_opt.unwrap();
-        match_slice = &page_str[start_byte_offset..end_byte_offset];
-        captures = re_expr
-            .captures(match_slice)
-            .expect(".is_some() must not be true");
-        raw_amount = String::from(
-            captures
-                .name("amount_with_commas")
-                .expect("already captured")
-                .as_str(),
+#[derive(Debug, Default)]
+pub(crate) struct ExtractedTransactions {
+    pub deposits: Vec<Deposit>,
+    pub card_purchases: Vec<DebitCardPurchase>,
+    pub payments: Vec<OnlinePayment>,
+    pub transfers_out: Vec<TransferOut>,
+    pub fees: Vec<Fee>,
+}
+
+impl ExtractedTransactions {
+    /// The date and balance effect of every transaction, in extraction order by kind.
+    pub fn signed_amounts(&self) -> Vec<(NaiveDate, Money)> {
+        let mut signed_amounts: Vec<(NaiveDate, Money)> = vec![];
+        signed_amounts.extend(self.deposits.iter().map(|t| (t.date, t.signed_amount())));
+        signed_amounts.extend(
+            self.card_purchases
+                .iter()
+                .map(|t| (t.date, t.signed_amount())),
+        );
+        signed_amounts.extend(self.payments.iter().map(|t| (t.date, t.signed_amount())));
+        signed_amounts.extend(
+            self.transfers_out
+                .iter()
+                .map(|t| (t.date, t.signed_amount())),
         );
-        transaction_month_day_str =
-            String::from(captures.name("date").expect("already_captured").as_str());
-        let Some((transaction_month_str, transaction_day_str)) =
-            transaction_month_day_str.split_once("/")
-        else {
-            panic!(
-                "transaction_month_day_str {} not parsed.",
-                transaction_month_day_str
-            );
-        };
-        transaction_month = transaction_month_str
-            .parse::<u32>()
-            .expect("Not valid month string");
-        transaction_day = transaction_day_str
-            .parse::<u32>()
-            .expect("Not valid day string");
-        if statement_year != 0 {
-            transaction_year = statement_year;
-        } else {
-            transaction_year = current_year;
+        signed_amounts.extend(self.fees.iter().map(|t| (t.date, t.signed_amount())));
+        signed_amounts
+    }
+
+    /// The total of the transactions listed under `section`, whatever their kind, as
+    /// printed on the statement: withdrawals add up to a positive total.
+    pub fn section_total(
+        &self,
+        section: StatementSection,
+        currency: Currency,
+    ) -> Result<Money, CurrencyMismatch> {
+        fn listed_under<T: TransactionKindConst>(
+            transactions: &[T],
+            section: StatementSection,
+        ) -> impl Iterator<Item = Money> + '_ {
+            transactions
+                .iter()
+                .filter(move |t| t.source().section == section)
+                .map(|t| t.amount())
         }
-        transaction_date =
-            NaiveDate::from_ymd_opt(transaction_year, transaction_month, transaction_day)
-                .expect("from_ymd_opt() failed.");
-        purchase = DebitCardPurchase::new(transaction_date, &mut raw_amount);
-        purchases.push(purchase);
-        start_byte_offset = end_byte_offset + 1;
-        trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
+        Money::total(
+            currency,
+            listed_under(&self.deposits, section)
+                .chain(listed_under(&self.card_purchases, section))
+                .chain(listed_under(&self.payments, section))
+                .chain(listed_under(&self.transfers_out, section))
+                .chain(listed_under(&self.fees, section)),
+        )
     }
-    purchases
 }
 
-pub(crate) fn extract_online_payment_captures_for_re(
-    page_str: &str,
-    statement_year: i32,
-) -> Vec<OnlinePayment> {
-    let online_payment_re =
-    Regex::new(r"(?ms)(?<date>\d{2}\/\d{2})\s(.+?)(Xfer)(.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d).?$")
-        .unwrap();
-    let re_expr: Regex = online_payment_re;
-    let mut start_byte_offset: usize = 0;
-    let mut trans_byte_offset_opt: Option<usize>;
-    let mut end_byte_offset: usize;
-    let mut match_slice: &str;
-    let mut captures: Captures;
-    let mut raw_amount: String;
-    let mut transaction_month_day_str: String;
-    let mut transaction_month_str: &str;
-    let mut transaction_month: u32;
-    let mut transaction_day_str: &str;
-    let mut transaction_day: u32;
-    let current_date = chrono::Utc::now();
-    let current_year = current_date.year();
-    let mut transaction_year: i32;
-    let mut transaction_date: NaiveDate;
-    let mut payment: OnlinePayment;
-    let mut payments: Vec<OnlinePayment> = vec![];
-    trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
-    while trans_byte_offset_opt.is_some() {
-        end_byte_offset = trans_byte_offset_opt.unwrap();
-        match_slice = &page_str[start_byte_offset..end_byte_offset];
-        captures = re_expr
-            .captures(match_slice)
-            .expect(".is_some() must not be true");
-        raw_amount = String::from(
-            captures
-                .name("amount_with_commas")
-                .expect("already captured")
-                .as_str(),
-        );
-        transaction_month_day_str =
-            String::from(captures.name("date").expect("already_captured").as_str());
-        let Some((transaction_month_str, transaction_day_str)) =
-            transaction_month_day_str.split_once("/")
-        else {
-            panic!(
-                "transaction_month_day_str {} not parsed.",
-                transaction_month_day_str
-            );
-        };
-        transaction_month = transaction_month_str
-            .parse::<u32>()
-            .expect("Not valid month string");
-        transaction_day = transaction_day_str
-            .parse::<u32>()
-            .expect("Not valid day string");
-        if statement_year != 0 {
-            transaction_year = statement_year;
-        } else {
-            transaction_year = current_year;
+/// Where on a statement page a capture was found, used to locate parse errors.
+pub(crate) struct PageRef<'a> {
+    pub file: &'a Path,
+    pub page: usize,
+}
+
+impl PageRef<'_> {
+    pub fn location(&self, byte_offset: usize) -> SourceLocation {
+        SourceLocation {
+            file: self.file.to_path_buf(),
+            page: self.page,
+            byte_offset,
         }
-        transaction_date =
-            NaiveDate::from_ymd_opt(transaction_year, transaction_month, transaction_day)
-                .expect("from_ymd_opt() failed.");
-        payment = OnlinePayment::new(transaction_date, &mut raw_amount);
-        payments.push(payment);
-        start_byte_offset = end_byte_offset + 1;
-        trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
     }
-    payments
 }
 
-pub(crate) fn extract_transfers_out_captures_for_re(
-    page_str: &str,
-    statement_year: i32,
-) -> Vec<TransferOut> {
-    let transfer_out_re =
-    Regex::new(r"(?ms)(?<date>\d{2}\/\d{2})\s(.+?)(Online\sTransfer\sTo)(.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d).?$")
-        .unwrap();
-    let re_expr: Regex = transfer_out_re;
-    let mut start_byte_offset: usize = 0;
-    let mut trans_byte_offset_opt: Option<usize>;
-    let mut end_byte_offset: usize;
-    let mut match_slice: &str;
-    let mut captures: Captures;
-    let mut raw_amount: String;
-    let mut transaction_month_day_str: String;
-    let mut transaction_month_str: &str;
-    let mut transaction_month: u32;
-    let mut transaction_day_str: &str;
-    let mut transaction_day: u32;
-    let current_date = chrono::Utc::now();
-    let current_year = current_date.year();
-    let mut transaction_year: i32;
-    let mut transaction_date: NaiveDate;
-    let mut transfer: TransferOut;
-    let mut transfers: Vec<TransferOut> = vec![];
-    trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
-    while trans_byte_offset_opt.is_some() {
-        end_byte_offset = trans_byte_offset_opt.unwrap();
-        match_slice = &page_str[start_byte_offset..end_byte_offset];
-        captures = re_expr
-            .captures(match_slice)
-            .expect(".is_some() must not be true");
-        raw_amount = String::from(
-            captures
-                .name("amount_with_commas")
-                .expect("already captured")
-                .as_str(),
-        );
-        transaction_month_day_str =
-            String::from(captures.name("date").expect("already_captured").as_str());
-        let Some((transaction_month_str, transaction_day_str)) =
-            transaction_month_day_str.split_once("/")
-        else {
-            panic!(
-                "transaction_month_day_str {} not parsed.",
-                transaction_month_day_str
-            );
-        };
-        transaction_month = transaction_month_str
-            .parse::<u32>()
-            .expect("Not valid month string");
-        transaction_day = transaction_day_str
-            .parse::<u32>()
-            .expect("Not valid day string");
-        if statement_year != 0 {
-            transaction_year = statement_year;
-        } else {
-            transaction_year = current_year;
+pub(crate) fn parse_begin_or_end_bal_amt(
+    bal_captures: Captures,
+    profile: &ParsingProfile,
+    page_ref: &PageRef,
+) -> Result<Money, ParseError> {
+    let bal_capture_match = bal_captures.name("amount").expect("group is not optional");
+    parse_money_token(bal_capture_match.as_str(), &profile.locale)
+        .map(|token| Money::new(token.balance_amount(), profile.currency))
+        .map_err(|_| ParseError::InvalidAmount {
+            location: page_ref.location(bal_capture_match.start()),
+            text: String::from(bal_capture_match.as_str()),
+        })
+}
+
+/// Parses the day and month of a transaction capture into a date within `period`.
+pub(crate) fn parse_transaction_date(
+    transaction_month_day_str: &str,
+    period: &StatementPeriod,
+    locale: &LocaleSettings,
+    location: SourceLocation,
+) -> Result<NaiveDate, ParseError> {
+    let invalid_date = || ParseError::InvalidDate {
+        location: location.clone(),
+        text: String::from(transaction_month_day_str),
+    };
+    let (transaction_month_str, transaction_day_str) = locale
+        .split_month_day(transaction_month_day_str)
+        .ok_or_else(invalid_date)?;
+    let transaction_month = transaction_month_str
+        .parse::<u32>()
+        .map_err(|_| invalid_date())?;
+    let transaction_day = transaction_day_str
+        .parse::<u32>()
+        .map_err(|_| invalid_date())?;
+    // 2000 is a leap year, so any day and month that exist in some year pass.
+    NaiveDate::from_ymd_opt(2000, transaction_month, transaction_day).ok_or_else(invalid_date)?;
+    period
+        .date_of(transaction_month, transaction_day)
+        .ok_or_else(|| ParseError::DateOutsidePeriod {
+            location: location.clone(),
+            text: String::from(transaction_month_day_str),
+            period: *period,
+        })
+}
+
+/// A keyword rule that refines the subtype of an entry.
+pub(crate) struct SubtypeRule {
+    /// Names the rule in a transaction's provenance.
+    pub name: &'static str,
+    pub subtype: TransactionSubtype,
+    pub re_expr: Regex,
+}
+
+/// Keyword rules that refine the subtype of an entry, in priority order.
+///
+/// A rule only applies to entries under a section with the same direction as its subtype,
+/// so a transfer listed under deposits is never read as a payment.
+pub(crate) fn subtype_rules(locale: &LocaleSettings) -> Vec<SubtypeRule> {
+    let date = locale.date_pattern();
+    let amount = amount_pattern(locale);
+    let card_purchase_re = Regex::new(&format!(
+        r"(?ms)(?<date>{date})\s(Recurring\sCard\sPurchase.+?)(?<amount>{amount})$"
+    ))
+    .unwrap();
+    let transfer_out_re = Regex::new(&format!(
+        r"(?ms)(?<date>{date})\s(.*?)(Online\sTransfer\sTo)(.+?)(?<amount>{amount}).?$"
+    ))
+    .unwrap();
+    let online_payment_re = Regex::new(&format!(
+        r"(?ms)(?<date>{date})\s(.*?)(Xfer)(.+?)(?<amount>{amount}).?$"
+    ))
+    .unwrap();
+    let wire_payment_re = Regex::new(&format!(
+        r"(?ms)(?<date>{date})\s(Orig\sCO\sName.+?)(Descr:Payments)(.+?)(?<amount>{amount})$"
+    ))
+    .unwrap();
+    vec![
+        SubtypeRule {
+            name: "card purchase",
+            subtype: TransactionSubtype::DebitCardPurchase,
+            re_expr: card_purchase_re,
+        },
+        SubtypeRule {
+            name: "online transfer out",
+            subtype: TransactionSubtype::TransferOut,
+            re_expr: transfer_out_re,
+        },
+        SubtypeRule {
+            name: "xfer payment",
+            subtype: TransactionSubtype::OnlinePayment,
+            re_expr: online_payment_re,
+        },
+        SubtypeRule {
+            name: "wire payment",
+            subtype: TransactionSubtype::Deposit,
+            re_expr: wire_payment_re,
+        },
+    ]
+}
+
+fn push_transaction<T: TransactionKindConst>(
+    transactions: &mut Vec<T>,
+    transaction_date: NaiveDate,
+    raw_amount: &str,
+    profile: &ParsingProfile,
+    source: Provenance,
+    warnings: &mut Vec<ParseError>,
+) {
+    let location = source.start.clone();
+    match T::new(transaction_date, raw_amount, profile, source) {
+        Ok(transaction) => transactions.push(transaction),
+        Err(_) => warnings.push(ParseError::InvalidAmount {
+            location,
+            text: String::from(raw_amount),
+        }),
+    }
+}
+
+impl ExtractedTransactions {
+    /// Adds the entry classified as `subtype` to the matching list.
+    ///
+    /// An entry whose amount cannot be parsed is skipped and reported in `warnings`.
+    pub fn push(
+        &mut self,
+        subtype: TransactionSubtype,
+        transaction_date: NaiveDate,
+        raw_amount: &str,
+        profile: &ParsingProfile,
+        source: Provenance,
+        warnings: &mut Vec<ParseError>,
+    ) {
+        match subtype {
+            TransactionSubtype::Deposit => push_transaction(
+                &mut self.deposits,
+                transaction_date,
+                raw_amount,
+                profile,
+                source,
+                warnings,
+            ),
+            TransactionSubtype::DebitCardPurchase => push_transaction(
+                &mut self.card_purchases,
+                transaction_date,
+                raw_amount,
+                profile,
+                source,
+                warnings,
+            ),
+            TransactionSubtype::OnlinePayment => push_transaction(
+                &mut self.payments,
+                transaction_date,
+                raw_amount,
+                profile,
+                source,
+                warnings,
+            ),
+            TransactionSubtype::TransferOut => push_transaction(
+                &mut self.transfers_out,
+                transaction_date,
+                raw_amount,
+                profile,
+                source,
+                warnings,
+            ),
+            TransactionSubtype::Fee => push_transaction(
+                &mut self.fees,
+                transaction_date,
+                raw_amount,
+                profile,
+                source,
+                warnings,
+            ),
         }
-        transaction_date =
-            NaiveDate::from_ymd_opt(transaction_year, transaction_month, transaction_day)
-                .expect("from_ymd_opt() failed.");
-        transfer = TransferOut::new(transaction_date, &mut raw_amount);
-        transfers.push(transfer);
-        start_byte_offset = end_byte_offset + 1;
-        trans_byte_offset_opt = re_expr.shortest_match_at(page_str, start_byte_offset);
     }
-    transfers
 }
@@ -0,0 +1,128 @@
+/// Text size and line spacing of generated pages, in points.
+const FONT_SIZE: usize = 9;
+const LEADING: usize = 11;
+const PAGE_WIDTH: usize = 612;
+const PAGE_HEIGHT: usize = 792;
+const MARGIN: usize = 36;
+
+/// The most lines that fit on one generated page.
+pub(crate) const MAX_LINES_PER_PAGE: usize = (PAGE_HEIGHT - 2 * MARGIN) / LEADING;
+
+/// Escapes a line for a PDF literal string. Characters outside printable ASCII, which the
+/// standard Courier font cannot show, become `?`.
+fn pdf_string(line: &str) -> String {
+    let mut escaped = String::with_capacity(line.len() + 2);
+    escaped.push('(');
+    for c in line.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped.push(')');
+    escaped
+}
+
+/// A minimal PDF with one page per entry of `pdf_page_strs`, each line of which is set in
+/// Courier from the top of the page, so pypdf extracts the same lines again.
+///
+/// Lines past [`MAX_LINES_PER_PAGE`] run off the bottom of the page.
+pub(crate) fn write_text_pdf(pdf_page_strs: &[String]) -> Vec<u8> {
+    let page_count = pdf_page_strs.len();
+    let font_id = 3;
+    let first_page_id = 4;
+    let mut objects: Vec<String> = vec![
+        String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..page_count)
+                .map(|page_index| format!("{} 0 R", first_page_id + 2 * page_index))
+                .collect::<Vec<String>>()
+                .join(" "),
+            page_count
+        ),
+        String::from("<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>"),
+    ];
+    for (page_index, page_str) in pdf_page_strs.iter().enumerate() {
+        let mut content = format!(
+            "BT\n/F1 {} Tf\n{} TL\n{} {} Td\n",
+            FONT_SIZE,
+            LEADING,
+            MARGIN,
+            PAGE_HEIGHT - MARGIN
+        );
+        for line in page_str.lines() {
+            content.push_str(&pdf_string(line));
+            content.push_str(" Tj T*\n");
+        }
+        content.push_str("ET\n");
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 {} 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            font_id,
+            first_page_id + 2 * page_index + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets: Vec<usize> = vec![];
+    for (object_index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", object_index + 1, object));
+    }
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xref_points_at_every_object() {
+        let pdf = write_text_pdf(&[
+            String::from("FEES\n01/31 Monthly Service Fee (waived) $0.00\n"),
+            String::from("Page 2 of 2\n"),
+        ]);
+        let pdf = String::from_utf8(pdf).unwrap();
+        let xref_offset: usize = pdf
+            .lines()
+            .skip_while(|line| *line != "startxref")
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let xref_entries: Vec<&str> = pdf[xref_offset..].lines().skip(3).take(7).collect();
+        for (object_index, entry) in xref_entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(
+                pdf[offset..].starts_with(&format!("{} 0 obj", object_index + 1)),
+                "object {}",
+                object_index + 1
+            );
+        }
+        assert!(pdf.contains("(01/31 Monthly Service Fee \\(waived\\) $0.00) Tj T*"));
+    }
+}
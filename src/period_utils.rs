@@ -0,0 +1,164 @@
+use crate::errors::ParseError;
+use crate::parse_utils::PageRef;
+use crate::profile::LocaleSettings;
+use chrono::{Datelike, NaiveDate};
+use regex::Captures;
+use std::fmt;
+
+/// Regex matching the statement period as written in `locale`, such as
+/// `December 15, 2023 through January 14, 2024`, with both dates captured.
+pub(crate) fn period_pattern(locale: &LocaleSettings) -> String {
+    format!(
+        r"(?<start>{})\s+{}\s+(?<end>{})",
+        locale.written_date_pattern("start"),
+        regex::escape(&locale.labels.period_separator),
+        locale.written_date_pattern("end")
+    )
+}
+
+/// The first and last day a statement covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StatementPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl StatementPeriod {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// The date inside the period with the given month and day, which may fall in either
+    /// year of a period that spans New Year.
+    pub fn date_of(&self, month: u32, day: u32) -> Option<NaiveDate> {
+        (self.start.year()..=self.end.year())
+            .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+            .find(|date| self.contains(*date))
+    }
+}
+
+impl fmt::Display for StatementPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} through {}", self.start, self.end)
+    }
+}
+
+pub(crate) fn parse_statement_period(
+    period_captures: Captures,
+    locale: &LocaleSettings,
+    page_ref: &PageRef,
+) -> Result<StatementPeriod, ParseError> {
+    let period_match = period_captures.get(0).expect("group 0 is the whole match");
+    let invalid_period = || ParseError::InvalidPeriod {
+        location: page_ref.location(period_match.start()),
+        text: String::from(period_match.as_str()),
+    };
+    let parse_date = |name: &str| {
+        let month = locale.month_number(&period_captures[format!("{name}_month").as_str()]);
+        let day = period_captures[format!("{name}_day").as_str()].parse().ok();
+        let year = period_captures[format!("{name}_year").as_str()]
+            .parse()
+            .ok();
+        match (year, month, day) {
+            (Some(year), Some(month), Some(day)) => NaiveDate::from_ymd_opt(year, month, day),
+            _ => None,
+        }
+        .ok_or_else(invalid_period)
+    };
+    let start = parse_date("start")?;
+    let end = parse_date("end")?;
+    if end < start {
+        return Err(invalid_period());
+    }
+    Ok(StatementPeriod { start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_utils::parse_transaction_date;
+    use crate::profile::LocaleSettings;
+    use regex::Regex;
+    use std::path::Path;
+
+    fn parse_period(text: &str) -> Result<StatementPeriod, ParseError> {
+        parse_locale_period(text, &LocaleSettings::us())
+    }
+
+    fn parse_locale_period(
+        text: &str,
+        locale: &LocaleSettings,
+    ) -> Result<StatementPeriod, ParseError> {
+        let period_re = Regex::new(&period_pattern(locale)).unwrap();
+        let page_ref = PageRef {
+            file: Path::new("sample.pdf"),
+            page: 1,
+        };
+        parse_statement_period(period_re.captures(text).unwrap(), locale, &page_ref)
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn places_month_and_day_in_the_period_year() {
+        let period = parse_period("December 15, 2023 through January 14, 2024").unwrap();
+        assert_eq!(period.date_of(12, 20), Some(date("2023-12-20")));
+        assert_eq!(period.date_of(1, 5), Some(date("2024-01-05")));
+        assert_eq!(period.date_of(6, 1), None);
+
+        let leap_year = parse_period("February 1, 2024 through February 29, 2024").unwrap();
+        assert_eq!(leap_year.date_of(2, 29), Some(date("2024-02-29")));
+    }
+
+    #[test]
+    fn reads_periods_written_in_the_locale() {
+        let european = LocaleSettings::european();
+        let period = parse_locale_period("15. Dezember 2023 bis 14. Januar 2024", &european);
+        assert_eq!(
+            period.map(|period| (period.start, period.end)),
+            Ok((date("2023-12-15"), date("2024-01-14")))
+        );
+        let spaced = parse_locale_period("1.  März 2024\nbis 31. März 2024", &european);
+        assert_eq!(spaced.map(|period| period.end), Ok(date("2024-03-31")));
+        assert!(!Regex::new(&period_pattern(&european))
+            .unwrap()
+            .is_match("January 1, 2024 through January 31, 2024"));
+    }
+
+    #[test]
+    fn rejects_unreadable_or_reversed_periods() {
+        assert!(matches!(
+            parse_period("Januar 1, 2024 through January 31, 2024"),
+            Err(ParseError::InvalidPeriod { .. })
+        ));
+        assert!(matches!(
+            parse_period("January 31, 2024 through January 1, 2024"),
+            Err(ParseError::InvalidPeriod { .. })
+        ));
+    }
+
+    #[test]
+    fn transaction_dates_outside_the_period_are_rejected() {
+        let period = parse_period("January 1, 2024 through January 31, 2024").unwrap();
+        let locale = LocaleSettings::us();
+        let location = PageRef {
+            file: Path::new("sample.pdf"),
+            page: 1,
+        }
+        .location(0);
+        assert_eq!(
+            parse_transaction_date("01/31", &period, &locale, location.clone()),
+            Ok(date("2024-01-31"))
+        );
+        assert!(matches!(
+            parse_transaction_date("02/01", &period, &locale, location.clone()),
+            Err(ParseError::DateOutsidePeriod { .. })
+        ));
+        assert!(matches!(
+            parse_transaction_date("13/01", &period, &locale, location),
+            Err(ParseError::InvalidDate { .. })
+        ));
+    }
+}
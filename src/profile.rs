@@ -0,0 +1,271 @@
+use crate::money_utils::Currency;
+use crate::section_utils::StatementSection;
+use clap::ValueEnum;
+
+/// The order of day and month in a printed transaction date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DateOrder {
+    MonthDay,
+    DayMonth,
+}
+
+/// The words a bank prints around the numbers on its statements.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StatementLabels {
+    /// Month names in calendar order, as written in the statement period.
+    pub month_names: Vec<String>,
+    /// The word between the first and last day of the statement period.
+    pub period_separator: String,
+    /// The heading printed above the entries of each section.
+    pub headings: Vec<(StatementSection, String)>,
+    /// The label of each section's total in the summary box.
+    pub summary_labels: Vec<(StatementSection, String)>,
+    /// The word starting the line that closes a section.
+    pub section_total: String,
+    pub beginning_balance: String,
+    pub ending_balance: String,
+    /// The labels the account number may follow, longest first.
+    pub account_number: Vec<String>,
+    /// The two words of a `Page X of Y` footer.
+    pub page_of: (String, String),
+    /// The word in parentheses after a heading repeated on the next page.
+    pub continued: String,
+    /// The column headings printed above the entries.
+    pub column_headings: Vec<String>,
+}
+
+impl StatementLabels {
+    pub fn section_for_heading(&self, heading: &str) -> Option<StatementSection> {
+        Self::find_section(&self.headings, heading)
+    }
+
+    pub fn section_for_summary_label(&self, label: &str) -> Option<StatementSection> {
+        Self::find_section(&self.summary_labels, label)
+    }
+
+    fn find_section(labels: &[(StatementSection, String)], text: &str) -> Option<StatementSection> {
+        labels
+            .iter()
+            .find(|(_, label)| label.split_whitespace().eq(text.split_whitespace()))
+            .map(|(section, _)| *section)
+    }
+}
+
+/// Regex fragment matching one of `labels` as printed, with any whitespace between words.
+pub(crate) fn labels_pattern<S: AsRef<str>>(labels: &[S]) -> String {
+    labels
+        .iter()
+        .map(|label| {
+            label
+                .as_ref()
+                .split_whitespace()
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(r"\s+")
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| String::from(*word)).collect()
+}
+
+/// How a bank prints numbers, dates and the words around them.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LocaleSettings {
+    pub date_order: DateOrder,
+    pub date_separator: char,
+    pub thousands_separator: char,
+    pub decimal_separator: char,
+    pub currency_symbols: Vec<String>,
+    pub labels: StatementLabels,
+}
+
+impl LocaleSettings {
+    /// `01/31` dates, `$1,234.56` amounts and `January 1, 2024 through January 31, 2024`
+    /// periods, with English headings and labels.
+    pub fn us() -> Self {
+        let section_labels = |label: fn(&StatementSection) -> Option<&'static str>| {
+            StatementSection::ALL
+                .iter()
+                .filter_map(|section| label(section).map(|text| (*section, String::from(text))))
+                .collect()
+        };
+        Self {
+            date_order: DateOrder::MonthDay,
+            date_separator: '/',
+            thousands_separator: ',',
+            decimal_separator: '.',
+            currency_symbols: vec![String::from("$")],
+            labels: StatementLabels {
+                month_names: strings(&[
+                    "January",
+                    "February",
+                    "March",
+                    "April",
+                    "May",
+                    "June",
+                    "July",
+                    "August",
+                    "September",
+                    "October",
+                    "November",
+                    "December",
+                ]),
+                period_separator: String::from("through"),
+                headings: section_labels(|section| Some(section.heading())),
+                summary_labels: section_labels(StatementSection::summary_label),
+                section_total: String::from("Total"),
+                beginning_balance: String::from("Beginning Balance"),
+                ending_balance: String::from("Ending Balance"),
+                account_number: strings(&["Account Number", "Account No.", "Account"]),
+                page_of: (String::from("Page"), String::from("of")),
+                continued: String::from("continued"),
+                column_headings: strings(&["DATE", "DESCRIPTION", "AMOUNT"]),
+            },
+        }
+    }
+
+    /// `31.01.` dates, `1.234,56 €` amounts and `1. Januar 2024 bis 31. Januar 2024`
+    /// periods, with German headings and labels.
+    pub fn european() -> Self {
+        Self {
+            date_order: DateOrder::DayMonth,
+            date_separator: '.',
+            thousands_separator: '.',
+            decimal_separator: ',',
+            currency_symbols: vec![String::from("€"), String::from("EUR")],
+            labels: StatementLabels {
+                month_names: strings(&[
+                    "Januar",
+                    "Februar",
+                    "März",
+                    "April",
+                    "Mai",
+                    "Juni",
+                    "Juli",
+                    "August",
+                    "September",
+                    "Oktober",
+                    "November",
+                    "Dezember",
+                ]),
+                period_separator: String::from("bis"),
+                headings: vec![
+                    (StatementSection::Summary, String::from("KONTOÜBERSICHT")),
+                    (
+                        StatementSection::DepositsAndAdditions,
+                        String::from("GUTSCHRIFTEN"),
+                    ),
+                    (
+                        StatementSection::AtmAndDebitCardWithdrawals,
+                        String::from("KARTENZAHLUNGEN UND ABHEBUNGEN"),
+                    ),
+                    (
+                        StatementSection::ElectronicWithdrawals,
+                        String::from("ÜBERWEISUNGEN UND LASTSCHRIFTEN"),
+                    ),
+                    (StatementSection::Fees, String::from("ENTGELTE")),
+                    (
+                        StatementSection::DailyEndingBalance,
+                        String::from("TAGESENDSALDO"),
+                    ),
+                ],
+                summary_labels: vec![
+                    (
+                        StatementSection::DepositsAndAdditions,
+                        String::from("Gutschriften"),
+                    ),
+                    (
+                        StatementSection::AtmAndDebitCardWithdrawals,
+                        String::from("Kartenzahlungen und Abhebungen"),
+                    ),
+                    (
+                        StatementSection::ElectronicWithdrawals,
+                        String::from("Überweisungen und Lastschriften"),
+                    ),
+                    (StatementSection::Fees, String::from("Entgelte")),
+                ],
+                section_total: String::from("Summe"),
+                beginning_balance: String::from("Anfangssaldo"),
+                ending_balance: String::from("Endsaldo"),
+                account_number: strings(&["Kontonummer", "Konto-Nr.", "Konto"]),
+                page_of: (String::from("Seite"), String::from("von")),
+                continued: String::from("Fortsetzung"),
+                column_headings: strings(&["DATUM", "BESCHREIBUNG", "BETRAG"]),
+            },
+        }
+    }
+
+    /// Regex fragment matching a day and month without year, such as `01/31` or `31.01.`.
+    pub fn date_pattern(&self) -> String {
+        let separator = regex::escape(&self.date_separator.to_string());
+        format!(r"\d{{2}}{separator}\d{{2}}(?:{separator})?")
+    }
+
+    /// Regex fragment matching a date written with the month's name, such as
+    /// `January 31, 2024` or `31. Januar 2024`, with its parts captured as `{name}_day`,
+    /// `{name}_month` and `{name}_year`.
+    ///
+    /// Any word is taken for the month, so a misspelt one is reported rather than missed.
+    pub fn written_date_pattern(&self, name: &str) -> String {
+        let day = format!(r"(?<{name}_day>\d{{1,2}})");
+        let month = format!(r"(?<{name}_month>\p{{Lu}}\p{{Ll}}+)");
+        let year = format!(r"(?<{name}_year>\d{{4}})");
+        match self.date_order {
+            DateOrder::MonthDay => format!(r"{month}\s+{day},\s+{year}"),
+            DateOrder::DayMonth => format!(r"{day}\.\s+{month}\s+{year}"),
+        }
+    }
+
+    /// The number of the month printed as `month_name`, counting January as 1.
+    pub fn month_number(&self, month_name: &str) -> Option<u32> {
+        let index = self
+            .labels
+            .month_names
+            .iter()
+            .position(|name| name == month_name)?;
+        u32::try_from(index + 1).ok()
+    }
+
+    /// Splits a date matched by [`LocaleSettings::date_pattern`] into month and day strings.
+    pub fn split_month_day<'a>(&self, date_str: &'a str) -> Option<(&'a str, &'a str)> {
+        let trimmed = date_str.trim().trim_end_matches(self.date_separator);
+        let (first, second) = trimmed.split_once(self.date_separator)?;
+        match self.date_order {
+            DateOrder::MonthDay => Some((first, second)),
+            DateOrder::DayMonth => Some((second, first)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub(crate) enum LocalePreset {
+    Us,
+    Eu,
+}
+
+/// Settings that adapt parsing to how a particular bank prints its statements.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ParsingProfile {
+    pub locale: LocaleSettings,
+    /// The currency of the account the statements belong to.
+    pub currency: Currency,
+}
+
+impl ParsingProfile {
+    pub fn for_locale(preset: LocalePreset) -> Self {
+        let (locale, currency) = match preset {
+            LocalePreset::Us => (LocaleSettings::us(), Currency::Usd),
+            LocalePreset::Eu => (LocaleSettings::european(), Currency::Eur),
+        };
+        Self { locale, currency }
+    }
+}
+
+impl Default for ParsingProfile {
+    fn default() -> Self {
+        Self::for_locale(LocalePreset::Us)
+    }
+}
@@ -0,0 +1,58 @@
+use crate::errors::SourceLocation;
+use crate::section_utils::StatementSection;
+use std::fmt;
+
+/// Where an extracted transaction came from, so any figure can be traced back to the PDF.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Provenance {
+    /// Start of the matched text in the page text of the source file.
+    pub start: SourceLocation,
+    /// End of the matched text, exclusive. It is on a later page than `start` when the
+    /// transaction wraps across a page break.
+    pub end: SourceLocation,
+    /// The statement text the transaction was read from, with page headers and footers
+    /// removed.
+    pub raw_text: String,
+    /// The heading the transaction is listed under.
+    pub section: StatementSection,
+    /// The name of the rule that classified the transaction.
+    pub rule: String,
+}
+
+impl Provenance {
+    pub fn page(&self) -> usize {
+        self.start.page
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start.page == self.end.page {
+            write!(
+                f,
+                "{} page {} bytes {}..{}",
+                self.start.file.display(),
+                self.start.page,
+                self.start.byte_offset,
+                self.end.byte_offset
+            )?;
+        } else {
+            write!(
+                f,
+                "{} page {} byte {} to page {} byte {}",
+                self.start.file.display(),
+                self.start.page,
+                self.start.byte_offset,
+                self.end.page,
+                self.end.byte_offset
+            )?;
+        }
+        write!(
+            f,
+            " under {}, rule {:?}: {:?}",
+            self.section.heading(),
+            self.rule,
+            self.raw_text
+        )
+    }
+}
@@ -1,10 +1,10 @@
+use crate::subprocess_pdf_service::PythonCommand;
 use pyo3::prelude::*;
 use std::path::PathBuf;
 
 pub(crate) fn get_page_count(pdf_file_path: &PathBuf) -> Result<u8, Box<dyn std::error::Error>> {
     Python::with_gil(|py| {
-        let pdf_parser = PyModule::import(py, "statements_to_books.pdf_parser")
-            .expect("unable to import 'pdf_parser'");
+        let pdf_parser = PyModule::import(py, "statements_to_books.pdf_parser")?;
         let result: u8 = pdf_parser
             .getattr("page_count_of_pdf")?
             .call1((pdf_file_path,))?
@@ -18,8 +18,7 @@ pub(crate) fn extract_text_from_page(
     page: u8,
 ) -> Result<String, Box<dyn std::error::Error>> {
     Python::with_gil(|py| {
-        let pdf_parser = PyModule::import(py, "statements_to_books.pdf_parser")
-            .expect("unable to import 'pdf_parser'");
+        let pdf_parser = PyModule::import(py, "statements_to_books.pdf_parser")?;
         let result: String = pdf_parser
             .getattr("extract_text_from_pdf")?
             .call1((&pdf_file_path, page))?
@@ -27,3 +26,58 @@ pub(crate) fn extract_text_from_page(
         Ok(result)
     })
 }
+
+/// The interpreter this process embeds, started with the module search path it uses, so a
+/// Python process run with it imports the same pypdf and `statements_to_books`.
+///
+/// The embedded interpreter's `sys.executable` is whatever `python3` is first on `PATH`,
+/// which may be another installation, so the interpreter is found under `sys.prefix`.
+pub(crate) fn embedded_python() -> Result<PythonCommand, Box<dyn std::error::Error>> {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let sys = PyModule::import(py, "sys")?;
+        let prefix: PathBuf = sys.getattr("prefix")?.extract()?;
+        let (major, minor): (u8, u8) = (
+            sys.getattr("version_info")?.getattr("major")?.extract()?,
+            sys.getattr("version_info")?.getattr("minor")?.extract()?,
+        );
+        let program = if cfg!(windows) {
+            prefix.join("python.exe")
+        } else {
+            prefix
+                .join("bin")
+                .join(format!("python{}.{}", major, minor))
+        };
+        let program = if program.is_file() {
+            program
+        } else {
+            sys.getattr("executable")?.extract()?
+        };
+        let search_path: Vec<PathBuf> = sys.getattr("path")?.extract()?;
+        Ok(PythonCommand {
+            program,
+            search_path: Some(std::env::join_paths(search_path)?),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn embedded_python_runs_the_linked_version() {
+        let python = embedded_python().unwrap();
+        let embedded_version: String = Python::with_gil(|py| {
+            let sys = PyModule::import(py, "sys").unwrap();
+            sys.getattr("version").unwrap().extract().unwrap()
+        });
+        let output = Command::new(&python.program)
+            .args(["-c", "import sys; print(sys.version, end='')"])
+            .env("PYTHONPATH", python.search_path.unwrap())
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), embedded_version);
+    }
+}
@@ -0,0 +1,682 @@
+use crate::io_utils::PAGE_BREAK;
+use crate::money_utils::{amount_pattern, parse_money_token, Money};
+use crate::parse_utils::TransactionKindConst;
+use crate::profile::{LocaleSettings, ParsingProfile};
+use crate::rng_utils::XorShift64;
+use crate::scanner::StatementScanner;
+use crate::section_utils::StatementSection;
+use crate::statement::parse_statement_pages;
+use chrono::NaiveDate;
+use fastnum::D256;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+
+/// Words the parser depends on in every locale, such as the keywords of the subtype rules.
+/// The locale's headings, labels and month names are kept as well, and every other word is
+/// pseudonymized.
+const KEPT_WORDS: &[&str] = &[
+    "INSTANCES",
+    "Recurring",
+    "Card",
+    "Purchase",
+    "Online",
+    "Transfer",
+    "To",
+    "Xfer",
+    "Orig",
+    "CO",
+    "Name",
+    "ID",
+    "Descr",
+    "Payments",
+    "Sec",
+    "CCD",
+    "PPD",
+    "Trace",
+    "Transaction",
+    "Chk",
+    "Deposit",
+    "Fee",
+    "Monthly",
+    "Service",
+    "CR",
+    "DR",
+    "USD",
+    "EUR",
+];
+
+/// The words of every heading, label and month name the parser reads in `locale`.
+fn locale_words(locale: &LocaleSettings) -> Vec<String> {
+    let labels = &locale.labels;
+    let (page, of) = &labels.page_of;
+    let texts = labels
+        .month_names
+        .iter()
+        .chain(labels.headings.iter().map(|(_, heading)| heading))
+        .chain(labels.summary_labels.iter().map(|(_, label)| label))
+        .chain(&labels.account_number)
+        .chain(&labels.column_headings)
+        .chain([
+            &labels.period_separator,
+            &labels.section_total,
+            &labels.beginning_balance,
+            &labels.ending_balance,
+            page,
+            of,
+            &labels.continued,
+        ]);
+    let word_re = Regex::new(r"\p{L}+").unwrap();
+    texts
+        .flat_map(|text| {
+            word_re
+                .find_iter(text)
+                .map(|word| String::from(word.as_str()))
+        })
+        .collect()
+}
+
+/// The amount scale is kept in millionths.
+const MILLIONTHS: u128 = 1_000_000;
+
+const CONSONANTS: &[u8] = b"bcdfghjklmnprstvwz";
+const VOWELS: &[u8] = b"aeiou";
+
+/// Turns extracted statement text into a fixture that can be shared.
+///
+/// Names, addresses and other words the parser does not depend on are replaced with made-up
+/// words of the same length, and account and reference numbers of three or more digits with
+/// other digits. The same word or number gets the same replacement everywhere, including
+/// across statements redacted by the same `Redactor`. Dates and statement years are kept.
+///
+/// Transaction amounts and the beginning balance are multiplied by one seeded scale with six
+/// decimals and rounded to the cent, so the redacted amounts share no common factor that
+/// divides back to the real ones. The ending balance, the summary and section totals and the
+/// daily balances are then restated from the redacted transactions, keeping any difference
+/// the original statement had, scaled the same way, so a statement that reconciled still
+/// reconciles and one that did not still shows where. Statements redacted in order by the
+/// same `Redactor` keep carrying their balances from one to the next.
+pub(crate) struct Redactor {
+    rng: XorShift64,
+    /// Millionths every amount is multiplied by, between 3 and 97 and never a whole number.
+    amount_scale: u128,
+    scanner: StatementScanner,
+    /// The redacted ending balance of each statement redacted so far, in cents, by its real
+    /// one, so a statement that begins where an earlier one ends still does once redacted.
+    carried_balances: HashMap<i128, i128>,
+    token_re: Regex,
+    /// Dates written with the month's name, such as those of the statement period, whose
+    /// years are kept.
+    written_date_re: Regex,
+    kept_words: HashSet<String>,
+    pseudonyms: HashMap<String, String>,
+    used_pseudonyms: HashSet<String>,
+}
+
+impl Redactor {
+    pub fn new(seed: u64, profile: ParsingProfile) -> Self {
+        let mut rng = XorShift64::new(seed);
+        let mut amount_scale = rng.between(3_000_001, 96_999_999) as u128;
+        if amount_scale.is_multiple_of(MILLIONTHS) {
+            amount_scale += 1;
+        }
+        let locale = &profile.locale;
+        let token_re = Regex::new(&format!(
+            r"(?<amount>{})|(?<date>{})|(?<word>\p{{L}}+)|(?<digits>\d+)",
+            amount_pattern(locale),
+            locale.date_pattern()
+        ))
+        .unwrap();
+        let kept_words = KEPT_WORDS
+            .iter()
+            .map(|word| String::from(*word))
+            .chain(locale_words(locale))
+            .collect();
+        Self {
+            rng,
+            amount_scale,
+            token_re,
+            written_date_re: Regex::new(&locale.written_date_pattern("written")).unwrap(),
+            kept_words,
+            pseudonyms: HashMap::new(),
+            used_pseudonyms: HashSet::new(),
+            scanner: StatementScanner::new(profile),
+            carried_balances: HashMap::new(),
+        }
+    }
+
+    pub fn redact(&mut self, text: &str) -> String {
+        let restated = self.restated_amounts(text);
+        let decimal_separator = self.scanner.profile.locale.decimal_separator;
+        let mut redacted = String::with_capacity(text.len());
+        let mut copied_to: usize = 0;
+        let written_dates: Vec<Range<usize>> = self
+            .written_date_re
+            .find_iter(text)
+            .map(|written_date| written_date.range())
+            .collect();
+        let token_re = self.token_re.clone();
+        for captures in token_re.captures_iter(text) {
+            let token = captures.get(0).expect("group 0 is the whole match");
+            redacted.push_str(&text[copied_to..token.start()]);
+            copied_to = token.end();
+            if let Some(amount) = captures.name("amount") {
+                let first_digit = amount.start()
+                    + amount
+                        .as_str()
+                        .find(|c: char| c.is_ascii_digit())
+                        .unwrap_or(0);
+                // Restated amounts are in cents, which only fit amounts printed with them.
+                let restated_cents = restated
+                    .get(&first_digit)
+                    .filter(|_| amount.as_str().contains(decimal_separator));
+                redacted.push_str(&match restated_cents {
+                    Some(cents) => self.rewrite_amount(amount.as_str(), |_| cents.unsigned_abs()),
+                    None => {
+                        self.rewrite_amount(amount.as_str(), |minor_units| self.scale(minor_units))
+                    }
+                });
+            } else if captures.name("date").is_some() {
+                redacted.push_str(token.as_str());
+            } else if let Some(word) = captures.name("word") {
+                if self.kept_words.contains(word.as_str()) {
+                    redacted.push_str(word.as_str());
+                } else {
+                    redacted.push_str(&self.pseudonym(word.as_str(), Self::fake_word));
+                }
+            } else if let Some(digits) = captures.name("digits") {
+                let is_year = digits.len() == 4
+                    && written_dates
+                        .iter()
+                        .any(|written_date| written_date.contains(&digits.start()))
+                    && (1900..2100).contains(&digits.as_str().parse::<u32>().unwrap_or(0));
+                if digits.len() < 3 || is_year {
+                    redacted.push_str(digits.as_str());
+                } else {
+                    redacted.push_str(&self.pseudonym(digits.as_str(), Self::fake_digits));
+                }
+            }
+        }
+        redacted.push_str(&text[copied_to..]);
+        redacted
+    }
+
+    /// The replacement for `original`, made with `fake` the first time it is seen.
+    fn pseudonym(&mut self, original: &str, fake: fn(&mut XorShift64, &str) -> String) -> String {
+        if let Some(pseudonym) = self.pseudonyms.get(original) {
+            return pseudonym.clone();
+        }
+        // Short words and numbers have few possible replacements, so after a few tries a
+        // replacement already given to something else is accepted.
+        let mut attempts: usize = 0;
+        let pseudonym = loop {
+            let candidate = fake(&mut self.rng, original);
+            attempts += 1;
+            if candidate != original
+                && !self.kept_words.contains(candidate.as_str())
+                && (attempts > 16 || !self.used_pseudonyms.contains(&candidate))
+            {
+                break candidate;
+            }
+        };
+        self.used_pseudonyms.insert(pseudonym.clone());
+        self.pseudonyms
+            .insert(String::from(original), pseudonym.clone());
+        pseudonym
+    }
+
+    /// A pronounceable word with the length and capitalization of `original`.
+    fn fake_word(rng: &mut XorShift64, original: &str) -> String {
+        original
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let letters = if i % 2 == 0 { CONSONANTS } else { VOWELS };
+                let letter = letters[rng.below(letters.len() as u64) as usize] as char;
+                if c.is_uppercase() {
+                    letter.to_ascii_uppercase()
+                } else {
+                    letter
+                }
+            })
+            .collect()
+    }
+
+    /// Random digits with the length of `original`, without a leading zero unless it had one.
+    fn fake_digits(rng: &mut XorShift64, original: &str) -> String {
+        original
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let low = if i == 0 && c != '0' { 1 } else { 0 };
+                char::from(b'0' + rng.between(low, 9) as u8)
+            })
+            .collect()
+    }
+
+    /// `minor_units` multiplied by the amount scale, rounded half up to whole minor units.
+    fn scale(&self, minor_units: u128) -> u128 {
+        (minor_units * self.amount_scale + MILLIONTHS / 2) / MILLIONTHS
+    }
+
+    /// [`Self::scale`] for an amount that may be negative, rounded away from zero on a half,
+    /// so an amount and its negation scale to opposite amounts.
+    fn scale_signed(&self, minor_units: i128) -> i128 {
+        let scaled = self.scale(minor_units.unsigned_abs()) as i128;
+        if minor_units < 0 {
+            -scaled
+        } else {
+            scaled
+        }
+    }
+
+    /// The redacted amounts, in cents, of the ending balance, the summary and section totals
+    /// and the daily balances of the statement in `text`, keyed by the offset in `text` of
+    /// their first digit.
+    ///
+    /// Each is the same sum of the redacted transactions and beginning balance that the
+    /// original is of the real ones, plus the original's difference from that sum, scaled.
+    /// A beginning balance that is the ending balance of a statement redacted earlier is
+    /// restated as that statement's redacted ending balance.
+    /// Nothing is restated when the statement cannot be parsed, which leaves every amount
+    /// scaled on its own.
+    fn restated_amounts(&mut self, text: &str) -> HashMap<usize, i128> {
+        let mut restated: HashMap<usize, i128> = HashMap::new();
+        let pages: Vec<String> = text.split(PAGE_BREAK).map(String::from).collect();
+        let Ok(statement) = parse_statement_pages(
+            Path::new("redacted"),
+            &pages,
+            &self.scanner,
+            NaiveDate::MAX,
+            vec![],
+        ) else {
+            return restated;
+        };
+        let mut page_starts: Vec<usize> = vec![];
+        let mut page_start: usize = 0;
+        for page in &pages {
+            page_starts.push(page_start);
+            page_start += page.len() + PAGE_BREAK.len_utf8();
+        }
+        let first_digit_at = |offset: usize| {
+            offset
+                + text[offset..]
+                    .find(|c: char| c.is_ascii_digit())
+                    .unwrap_or(0)
+        };
+        let locale = &self.scanner.profile.locale;
+
+        // Each transaction's section, date, amount as listed and amount as it affects the
+        // balance, in cents.
+        let mut transactions: Vec<(StatementSection, NaiveDate, i128, i128)> = vec![];
+        fn listed<T: TransactionKindConst>(
+            transactions: &[T],
+        ) -> impl Iterator<Item = (StatementSection, NaiveDate, Money, Money)> + '_ {
+            transactions
+                .iter()
+                .map(|t| (t.source().section, t.date(), t.amount(), t.signed_amount()))
+        }
+        let extracted = &statement.transactions;
+        for (section, date, amount, signed_amount) in listed(&extracted.deposits)
+            .chain(listed(&extracted.card_purchases))
+            .chain(listed(&extracted.payments))
+            .chain(listed(&extracted.transfers_out))
+            .chain(listed(&extracted.fees))
+        {
+            let (Some(amount), Some(signed_amount)) = (cents(amount), cents(signed_amount)) else {
+                return restated;
+            };
+            transactions.push((section, date, amount, signed_amount));
+        }
+
+        for (page_num, page) in pages.iter().enumerate() {
+            let mut line_start: usize = 0;
+            for line in page.split_inclusive('\n') {
+                let line_offset = page_starts[page_num] + line_start;
+                line_start += line.len();
+                let trimmed = line.trim_start();
+                let total_prefix = trimmed
+                    .strip_prefix(locale.labels.section_total.as_str())
+                    .filter(|rest| rest.starts_with([' ', '\t']))
+                    .map(str::trim_start);
+                let (summary_line, summary_offset) = match total_prefix {
+                    Some(rest) => (rest, line_offset + line.len() - rest.len()),
+                    None => (line, line_offset),
+                };
+                let Some(captures) = self.scanner.summary_total_re.captures(summary_line) else {
+                    continue;
+                };
+                let Some(section) = locale.labels.section_for_summary_label(&captures["label"])
+                else {
+                    continue;
+                };
+                let amount = captures.name("amount").expect("group is not optional");
+                let Some(stated) = parse_money_token(amount.as_str(), locale)
+                    .ok()
+                    .and_then(|token| cents(Money::new(token.amount.abs(), statement.currency)))
+                else {
+                    continue;
+                };
+                let listed_under = transactions
+                    .iter()
+                    .filter(|(listed_section, ..)| *listed_section == section)
+                    .map(|(_, _, amount, _)| *amount);
+                let (computed, redacted) =
+                    listed_under.fold((0, 0), |(computed, redacted), cents| {
+                        (computed + cents, redacted + self.scale_signed(cents))
+                    });
+                restated.insert(
+                    first_digit_at(summary_offset + amount.start()),
+                    redacted + self.scale_signed(stated - computed),
+                );
+            }
+        }
+
+        let Some(begin_bal) = statement.begin_bal.and_then(cents) else {
+            return restated;
+        };
+        let redacted_begin_bal = self
+            .carried_balances
+            .get(&begin_bal)
+            .copied()
+            .unwrap_or_else(|| self.scale_signed(begin_bal));
+        for (page_num, page) in pages.iter().enumerate() {
+            for captures in self.scanner.begin_balance_re.captures_iter(page) {
+                let amount = captures.name("amount").expect("group is not optional");
+                restated.insert(
+                    first_digit_at(page_starts[page_num] + amount.start()),
+                    redacted_begin_bal,
+                );
+            }
+        }
+        let balance_through = |date: NaiveDate| {
+            transactions
+                .iter()
+                .filter(|(_, transaction_date, ..)| *transaction_date <= date)
+                .fold(
+                    (begin_bal, redacted_begin_bal),
+                    |(computed, redacted), (.., signed_amount)| {
+                        (
+                            computed + signed_amount,
+                            redacted + self.scale_signed(*signed_amount),
+                        )
+                    },
+                )
+        };
+        for daily_balance in &statement.daily_balances {
+            let Some(stated) = cents(daily_balance.balance) else {
+                continue;
+            };
+            let (computed, redacted) = balance_through(daily_balance.date);
+            let location = &daily_balance.location;
+            restated.insert(
+                first_digit_at(page_starts[location.page - 1] + location.byte_offset),
+                redacted + self.scale_signed(stated - computed),
+            );
+        }
+        if let Some(ending_bal) = statement.ending_bal.and_then(cents) {
+            let (computed, redacted) = balance_through(NaiveDate::MAX);
+            let redacted_ending_bal = redacted + self.scale_signed(ending_bal - computed);
+            for (page_num, page) in pages.iter().enumerate() {
+                for captures in self.scanner.end_balance_re.captures_iter(page) {
+                    let amount = captures.name("amount").expect("group is not optional");
+                    restated.insert(
+                        first_digit_at(page_starts[page_num] + amount.start()),
+                        redacted_ending_bal,
+                    );
+                }
+            }
+            self.carried_balances
+                .insert(ending_bal, redacted_ending_bal);
+        }
+        restated
+    }
+
+    /// Replaces the number in an amount token with `new_minor_units` of its minor units,
+    /// keeping its currency symbol, sign, thousands separators and number of decimals.
+    fn rewrite_amount(&self, token: &str, new_minor_units: impl FnOnce(u128) -> u128) -> String {
+        let locale = &self.scanner.profile.locale;
+        let (Some(first_digit), Some(last_digit)) = (
+            token.find(|c: char| c.is_ascii_digit()),
+            token.rfind(|c: char| c.is_ascii_digit()),
+        ) else {
+            return String::from(token);
+        };
+        let number = &token[first_digit..=last_digit];
+        let (whole, fraction) = number
+            .split_once(locale.decimal_separator)
+            .unwrap_or((number, ""));
+        // Amounts under a thousand cannot show whether the bank groups thousands; assume it
+        // does, as most statements do.
+        let grouped = whole.contains(locale.thousands_separator) || whole.len() <= 3;
+        let minor_units: String = whole
+            .chars()
+            .chain(fraction.chars())
+            .filter(|c| c.is_ascii_digit())
+            .collect();
+        let Ok(minor_units) = minor_units.parse::<u128>() else {
+            return String::from(token);
+        };
+        let scaled = new_minor_units(minor_units).to_string();
+        let scaled = format!("{:0>width$}", scaled, width = fraction.len() + 1);
+        let (scaled_whole, scaled_fraction) = scaled.split_at(scaled.len() - fraction.len());
+        let mut scaled_number = if grouped {
+            group_thousands(scaled_whole, locale.thousands_separator)
+        } else {
+            String::from(scaled_whole)
+        };
+        if !fraction.is_empty() {
+            scaled_number.push(locale.decimal_separator);
+            scaled_number.push_str(scaled_fraction);
+        }
+        format!(
+            "{}{}{}",
+            &token[..first_digit],
+            scaled_number,
+            &token[last_digit + 1..]
+        )
+    }
+}
+
+/// `money` in whole cents, if it has no finer fraction.
+fn cents(money: Money) -> Option<i128> {
+    i128::try_from(money.amount * D256::from(100)).ok()
+}
+
+fn group_thousands(digits: &str, separator: char) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{LocalePreset, ParsingProfile};
+    use crate::scanner::StatementScanner;
+    use crate::statement::{parse_statement_pages, StatementResult};
+    use crate::summary_utils::summary_mismatches;
+    use chrono::NaiveDate;
+    use std::path::Path;
+
+    const STATEMENT: &str = "ACME WIDGETS LLC\n1200 Harbor Road, Springfield 62704\nAccount Number: 000123456789\nJanuary 1, 2024 through January 31, 2024\nCHECKING SUMMARY\nINSTANCES AMOUNT\nBeginning Balance $1,000.00\nDeposits and Additions 2 $1,400.00\nElectronic Withdrawals 2 -$300.00\nFees 1 -$15.00\nEnding Balance $2,085.00\nDEPOSITS AND ADDITIONS\n01/05 Orig CO Name:Jane Doe Consulting Orig ID:1234567 Descr:Payments Sec:CCD\nTrace#:0210001234 $1,000.00\n01/20 Deposit Globex 400.00\nTotal Deposits and Additions $1,400.00\nELECTRONIC WITHDRAWALS\n01/10 Vendor Initech Bill Xfer Ref 88123 $100.00\n01/11 Online Transfer To Chk ...9876 Transaction#: 19876543 $200.00\nTotal Electronic Withdrawals $300.00\nFEES\n01/31 Monthly Service Fee $15.00\nTotal Fees $15.00\nDAILY ENDING BALANCE\n01/05 $2,000.00 01/10 1,900.00\n01/11 1,700.00 01/20 2,100.00\n01/31 2,085.00\nPage 1 of 1\n";
+
+    /// The same statement printed the European way, on two pages.
+    const EUROPEAN_STATEMENT: &str = "Musterfirma GmbH\nHafenstraße 12, 20457 Hamburg\nKontonummer: 000123456789\n1. Januar 2024 bis 31. Januar 2024\nKONTOÜBERSICHT\nAnfangssaldo 1.000,00 €\nGutschriften 2 1.400,00 €\nÜberweisungen und Lastschriften 2 -300,00 €\nEntgelte 1 -15,00 €\nEndsaldo 2.085,00 €\nGUTSCHRIFTEN\nDATUM BESCHREIBUNG BETRAG\n05.01. Orig CO Name:Erika Mustermann Beratung Orig ID:1234567 Descr:Payments Sec:CCD\nTrace#:0210001234 1.000,00 €\n20.01. Deposit Globex 400,00\nSumme Gutschriften 1.400,00 €\nSeite 1 von 2\n\u{c}Musterfirma GmbH\nÜBERWEISUNGEN UND LASTSCHRIFTEN\nDATUM BESCHREIBUNG BETRAG\n10.01. Lieferant Initech Bill Xfer Ref 88123 100,00 €\n11.01. Online Transfer To Chk ...9876 Transaction#: 19876543 200,00 €\nSumme Überweisungen und Lastschriften 300,00 €\nENTGELTE\n31.01. Monthly Service Fee 15,00 €\nSumme Entgelte 15,00 €\nTAGESENDSALDO\n05.01. 2.000,00 € 10.01. 1.900,00\n11.01. 1.700,00 20.01. 2.100,00\n31.01. 2.085,00\nSeite 2 von 2\n";
+
+    fn parse(text: &str) -> StatementResult {
+        parse_in(text, ParsingProfile::default())
+    }
+
+    fn parse_in(text: &str, profile: ParsingProfile) -> StatementResult {
+        let scanner = StatementScanner::new(profile);
+        let as_of = NaiveDate::from_ymd_opt(2024, 2, 15).unwrap();
+        let pages: Vec<String> = text.split(PAGE_BREAK).map(String::from).collect();
+        parse_statement_pages(Path::new("sample.pdf"), &pages, &scanner, as_of, vec![]).unwrap()
+    }
+
+    /// Checks that the summary box, the ending balance and the daily balances of `statement`
+    /// all agree with its transactions.
+    fn assert_reconciles(statement: &StatementResult) {
+        assert!(
+            summary_mismatches(&statement.summary_totals, &statement.transactions)
+                .unwrap()
+                .is_empty()
+        );
+        let begin_bal = statement.begin_bal.unwrap();
+        let ending_bal = statement.ending_bal.unwrap();
+        let net_change = statement
+            .transactions
+            .signed_amounts()
+            .iter()
+            .map(|(_, amount)| amount.amount)
+            .fold(fastnum::D256::ZERO, |total, amount| total + amount);
+        assert_eq!(ending_bal.amount - begin_bal.amount, net_change);
+        let divergences = crate::balance_utils::replay_daily_balances(
+            begin_bal,
+            &statement.transactions.signed_amounts(),
+            &statement.daily_balances,
+        )
+        .unwrap();
+        assert!(divergences.is_empty(), "{:?}", divergences);
+    }
+
+    #[test]
+    fn redacted_statement_keeps_its_reconciliation() {
+        let mut redactor = Redactor::new(7, ParsingProfile::default());
+        let redacted = redactor.redact(STATEMENT);
+        for secret in [
+            "ACME",
+            "Jane",
+            "Doe",
+            "Harbor",
+            "62704",
+            "123456789",
+            "9876",
+            " $1,400.00",
+        ] {
+            assert!(!redacted.contains(secret), "{:?} in\n{}", secret, redacted);
+        }
+        assert!(redacted.contains("January 1, 2024 through January 31, 2024"));
+        assert_eq!(
+            redactor.redact("ACME Jane 9876"),
+            redactor.redact("ACME Jane 9876")
+        );
+
+        let original = parse(STATEMENT);
+        let statement = parse(&redacted);
+        assert!(statement.warnings.is_empty(), "{:?}", statement.warnings);
+        assert!(statement.account_number.is_some());
+        assert_ne!(statement.account_number, original.account_number);
+        assert_eq!(
+            statement.transactions.signed_amounts().len(),
+            original.transactions.signed_amounts().len()
+        );
+        assert_reconciles(&statement);
+    }
+
+    #[test]
+    fn redacted_european_statement_still_parses_and_reconciles() {
+        let profile = ParsingProfile::for_locale(LocalePreset::Eu);
+        let original = parse_in(EUROPEAN_STATEMENT, profile.clone());
+        assert!(original.warnings.is_empty(), "{:?}", original.warnings);
+        assert_reconciles(&original);
+
+        let redacted = Redactor::new(7, profile.clone()).redact(EUROPEAN_STATEMENT);
+        for secret in [
+            "Musterfirma",
+            "Erika",
+            "Hafenstraße",
+            "20457",
+            "123456789",
+            "1.400,00",
+        ] {
+            assert!(!redacted.contains(secret), "{:?} in\n{}", secret, redacted);
+        }
+        for kept in [
+            "1. Januar 2024 bis 31. Januar 2024",
+            "KONTOÜBERSICHT",
+            "Überweisungen und Lastschriften",
+            "ÜBERWEISUNGEN UND LASTSCHRIFTEN",
+            "Summe Entgelte",
+            "TAGESENDSALDO",
+            "Seite 2 von 2",
+        ] {
+            assert!(redacted.contains(kept), "{:?} not in\n{}", kept, redacted);
+        }
+
+        let statement = parse_in(&redacted, profile);
+        assert!(statement.warnings.is_empty(), "{:?}", statement.warnings);
+        assert!(statement.is_complete(), "{:?}", statement.page_issues);
+        assert_eq!(statement.period, original.period);
+        assert!(statement.account_number.is_some());
+        assert_ne!(statement.account_number, original.account_number);
+        assert_eq!(
+            statement.transactions.signed_amounts().len(),
+            original.transactions.signed_amounts().len()
+        );
+        assert_eq!(statement.summary_totals.len(), 3);
+        assert_eq!(statement.daily_balances.len(), 5);
+        assert_reconciles(&statement);
+    }
+
+    fn transaction_cents(statement: &StatementResult) -> Vec<u128> {
+        statement
+            .transactions
+            .signed_amounts()
+            .iter()
+            .map(|(_, amount)| cents(*amount).unwrap().unsigned_abs())
+            .collect()
+    }
+
+    fn gcd(a: u128, b: u128) -> u128 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    #[test]
+    fn redacted_amounts_share_no_factor_that_divides_back_to_the_originals() {
+        let original = transaction_cents(&parse(STATEMENT));
+        for seed in 0..20 {
+            let redacted = transaction_cents(&parse(
+                &Redactor::new(seed, ParsingProfile::default()).redact(STATEMENT),
+            ));
+            for factor in 1..=10_000 {
+                assert!(
+                    !redacted
+                        .iter()
+                        .zip(&original)
+                        .all(|(redacted, original)| *redacted == original * factor),
+                    "seed {} scales by {}",
+                    seed,
+                    factor
+                );
+            }
+            // Dividing out the common factors leaves different amounts in the same ratios
+            // only if the redacted amounts are whole multiples of the originals.
+            let original_gcd = original.iter().fold(0, |g, cents| gcd(g, *cents));
+            let redacted_gcd = redacted.iter().fold(0, |g, cents| gcd(g, *cents));
+            assert_ne!(
+                redacted
+                    .iter()
+                    .map(|cents| cents / redacted_gcd)
+                    .collect::<Vec<_>>(),
+                original
+                    .iter()
+                    .map(|cents| cents / original_gcd)
+                    .collect::<Vec<_>>(),
+                "seed {}",
+                seed
+            );
+        }
+    }
+}
@@ -0,0 +1,1250 @@
+use crate::fx_utils::{ExchangeRates, FxError};
+use crate::ledger_utils::{
+    AccountType, EntrySide, Ledger, LedgerError, BANK_ACCOUNT, OPENING_BALANCE_EQUITY,
+    OWNER_CONTRIBUTIONS, OWNER_DRAWS, UNCLASSIFIED_TRANSFERS,
+};
+use crate::money_utils::{Currency, Money};
+use chrono::{Datelike, Months, NaiveDate};
+use clap::ValueEnum;
+use std::fmt;
+
+/// How a report's date range is split into columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ReportColumns {
+    Month,
+    Quarter,
+    /// One column for the whole range.
+    Total,
+}
+
+/// The dates one report column covers, both inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReportPeriod {
+    pub label: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// The twelve months the books are closed on, starting on the first of `start_month`.
+/// A fiscal year is named after the calendar year it ends in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FiscalYear {
+    /// 1 for January through 12 for December.
+    pub start_month: u32,
+}
+
+impl Default for FiscalYear {
+    /// The calendar year.
+    fn default() -> Self {
+        Self { start_month: 1 }
+    }
+}
+
+impl FiscalYear {
+    /// The first day of the fiscal year `date` falls in.
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        let year = if date.month() >= self.start_month {
+            date.year()
+        } else {
+            date.year() - 1
+        };
+        NaiveDate::from_ymd_opt(year, self.start_month, 1).unwrap()
+    }
+
+    /// The name of the fiscal year `date` falls in, such as `2024` for the calendar year
+    /// or `FY2024` for one ending in 2024.
+    pub fn name_of(&self, date: NaiveDate) -> String {
+        let last_day = self.start_of(date) + Months::new(12) - chrono::Days::new(1);
+        if self.start_month == 1 {
+            last_day.year().to_string()
+        } else {
+            format!("FY{}", last_day.year())
+        }
+    }
+
+    /// The fiscal quarter, 1 through 4, `date` falls in.
+    pub fn quarter_of(&self, date: NaiveDate) -> u32 {
+        let months_in = (date.month() + 12 - self.start_month) % 12;
+        months_in / 3 + 1
+    }
+}
+
+/// Splits `start` through `end` into calendar months or quarters of `fiscal_year`. The
+/// first and last columns are cut short where the range starts or ends inside them.
+pub(crate) fn report_periods(
+    start: NaiveDate,
+    end: NaiveDate,
+    columns: ReportColumns,
+    fiscal_year: FiscalYear,
+) -> Vec<ReportPeriod> {
+    let months_per_column = match columns {
+        ReportColumns::Month => 1,
+        ReportColumns::Quarter => 3,
+        ReportColumns::Total => {
+            return vec![ReportPeriod {
+                label: String::from("Total"),
+                start,
+                end,
+            }]
+        }
+    };
+    let mut periods = vec![];
+    let mut column_start = fiscal_year.start_of(start);
+    while column_start + Months::new(months_per_column) <= start {
+        column_start = column_start + Months::new(months_per_column);
+    }
+    while column_start <= end {
+        let next_start = column_start + Months::new(months_per_column);
+        let label = match columns {
+            ReportColumns::Month => format!("{}-{:02}", column_start.year(), column_start.month()),
+            _ => format!(
+                "{} Q{}",
+                fiscal_year.name_of(column_start),
+                fiscal_year.quarter_of(column_start)
+            ),
+        };
+        periods.push(ReportPeriod {
+            label,
+            start: column_start.max(start),
+            end: next_start.pred_opt().unwrap().min(end),
+        });
+        column_start = next_start;
+    }
+    periods
+}
+
+/// One account's row in a report, with an amount per column.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReportLine {
+    pub account: String,
+    pub amounts: Vec<Money>,
+}
+
+/// Revenue and expenses by category, with subtotals and net income, for each column of
+/// a date range.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct IncomeStatement {
+    pub currency: Currency,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub periods: Vec<ReportPeriod>,
+    /// Revenue accounts with any revenue in the range, in chart order.
+    pub revenue: Vec<ReportLine>,
+    pub total_revenue: Vec<Money>,
+    /// Expense accounts with any expense in the range, in chart order.
+    pub expenses: Vec<ReportLine>,
+    pub total_expenses: Vec<Money>,
+    pub net_income: Vec<Money>,
+}
+
+impl IncomeStatement {
+    pub fn from_ledger(
+        ledger: &Ledger,
+        start: NaiveDate,
+        end: NaiveDate,
+        columns: ReportColumns,
+        fiscal_year: FiscalYear,
+    ) -> Self {
+        let periods = report_periods(start, end, columns, fiscal_year);
+        let lines = |account_type: AccountType| -> Vec<ReportLine> {
+            let mut lines: Vec<ReportLine> = ledger
+                .account_balances(account_type)
+                .into_iter()
+                .map(|(account, _)| ReportLine {
+                    account: account.name.clone(),
+                    amounts: vec![],
+                })
+                .collect();
+            for period in &periods {
+                let balances =
+                    ledger.account_balances_between(account_type, period.start, period.end);
+                for (line, (_, balance)) in lines.iter_mut().zip(balances) {
+                    line.amounts.push(balance);
+                }
+            }
+            lines.retain(|line| line.amounts.iter().any(|amount| !amount.is_zero()));
+            lines
+        };
+        let totals = |account_type: AccountType| -> Vec<Money> {
+            periods
+                .iter()
+                .map(|period| ledger.total_between(account_type, period.start, period.end))
+                .collect()
+        };
+        let total_revenue = totals(AccountType::Revenue);
+        let total_expenses = totals(AccountType::Expense);
+        let net_income = total_revenue
+            .iter()
+            .zip(&total_expenses)
+            .map(|(revenue, expenses)| {
+                Money::new(revenue.amount - expenses.amount, ledger.currency)
+            })
+            .collect();
+        let revenue = lines(AccountType::Revenue);
+        let expenses = lines(AccountType::Expense);
+        Self {
+            currency: ledger.currency,
+            start,
+            end,
+            periods,
+            revenue,
+            total_revenue,
+            expenses,
+            total_expenses,
+            net_income,
+        }
+    }
+
+    /// Every row as label and amounts, in the order they are printed. Headings have no
+    /// amounts.
+    pub fn rows(&self) -> Vec<(String, Vec<Money>)> {
+        let mut rows = vec![(String::from("Revenue"), vec![])];
+        rows.extend(
+            self.revenue
+                .iter()
+                .map(|line| (format!("  {}", line.account), line.amounts.clone())),
+        );
+        rows.push((String::from("Total Revenue"), self.total_revenue.clone()));
+        rows.push((String::from("Expenses"), vec![]));
+        rows.extend(
+            self.expenses
+                .iter()
+                .map(|line| (format!("  {}", line.account), line.amounts.clone())),
+        );
+        rows.push((String::from("Total Expenses"), self.total_expenses.clone()));
+        rows.push((String::from("Net Income"), self.net_income.clone()));
+        rows
+    }
+
+    /// The rows as comma separated values, with a header of column labels.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("line");
+        for period in &self.periods {
+            csv.push_str(&format!(",{}", period.label));
+        }
+        csv.push('\n');
+        for (label, amounts) in self.rows() {
+            csv.push_str(label.trim());
+            for amount in amounts {
+                csv.push_str(&format!(",{:.2}", amount.amount));
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// A table with a column per period, amounts in the statement currency.
+impl fmt::Display for IncomeStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Income Statement {} through {} ({})",
+            self.start, self.end, self.currency
+        )?;
+        write!(f, "{:<26}", "")?;
+        for period in &self.periods {
+            write!(f, "{:>13}", period.label)?;
+        }
+        writeln!(f)?;
+        for (label, amounts) in self.rows() {
+            if amounts.is_empty() {
+                writeln!(f, "{}", label)?;
+                continue;
+            }
+            write!(f, "{:<26}", label)?;
+            for amount in amounts {
+                write!(f, "{:>13}", format!("{:.2}", amount.amount))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Revenue, expenses, net income and the owner's contributions and draws for one period.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PeriodTotals {
+    pub period: ReportPeriod,
+    pub revenue: Money,
+    pub expenses: Money,
+    pub net_income: Money,
+    pub contributions: Money,
+    pub draws: Money,
+}
+
+impl PeriodTotals {
+    pub fn from_ledger(ledger: &Ledger, period: ReportPeriod) -> Result<Self, LedgerError> {
+        let revenue = ledger.total_between(AccountType::Revenue, period.start, period.end);
+        let expenses = ledger.total_between(AccountType::Expense, period.start, period.end);
+        Ok(Self {
+            revenue,
+            expenses,
+            net_income: Money::new(revenue.amount - expenses.amount, ledger.currency),
+            contributions: ledger.balance_between(OWNER_CONTRIBUTIONS, period.start, period.end)?,
+            draws: ledger.balance_between(OWNER_DRAWS, period.start, period.end)?,
+            period,
+        })
+    }
+
+    fn amounts(&self) -> [Money; 5] {
+        [
+            self.revenue,
+            self.expenses,
+            self.net_income,
+            self.contributions,
+            self.draws,
+        ]
+    }
+
+    /// The totals converted into `to` at the rates in effect on the last day of the
+    /// period.
+    pub fn convert(&self, rates: &ExchangeRates, to: Currency) -> Result<Self, FxError> {
+        let date = self.period.end;
+        Ok(Self {
+            period: self.period.clone(),
+            revenue: rates.convert(self.revenue, to, date)?,
+            expenses: rates.convert(self.expenses, to, date)?,
+            net_income: rates.convert(self.net_income, to, date)?,
+            contributions: rates.convert(self.contributions, to, date)?,
+            draws: rates.convert(self.draws, to, date)?,
+        })
+    }
+}
+
+/// The totals of every statement combined, by month, by fiscal quarter, and for the
+/// fiscal year to date.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PeriodSummary {
+    pub currency: Currency,
+    pub months: Vec<PeriodTotals>,
+    pub quarters: Vec<PeriodTotals>,
+    /// From the start of the fiscal year the range ends in through the end of the range.
+    pub year_to_date: PeriodTotals,
+}
+
+impl PeriodSummary {
+    pub fn from_ledger(
+        ledger: &Ledger,
+        start: NaiveDate,
+        end: NaiveDate,
+        fiscal_year: FiscalYear,
+    ) -> Result<Self, LedgerError> {
+        let totals = |columns: ReportColumns| -> Result<Vec<PeriodTotals>, LedgerError> {
+            report_periods(start, end, columns, fiscal_year)
+                .into_iter()
+                .map(|period| PeriodTotals::from_ledger(ledger, period))
+                .collect()
+        };
+        let year_to_date = ReportPeriod {
+            label: format!("{} YTD", fiscal_year.name_of(end)),
+            start: fiscal_year.start_of(end),
+            end,
+        };
+        Ok(Self {
+            currency: ledger.currency,
+            months: totals(ReportColumns::Month)?,
+            quarters: totals(ReportColumns::Quarter)?,
+            year_to_date: PeriodTotals::from_ledger(ledger, year_to_date)?,
+        })
+    }
+
+    /// Every total converted into `to`, each at the rates of the last day of its period.
+    pub fn convert(&self, rates: &ExchangeRates, to: Currency) -> Result<Self, FxError> {
+        let convert_all = |totals: &[PeriodTotals]| -> Result<Vec<PeriodTotals>, FxError> {
+            totals.iter().map(|t| t.convert(rates, to)).collect()
+        };
+        Ok(Self {
+            currency: to,
+            months: convert_all(&self.months)?,
+            quarters: convert_all(&self.quarters)?,
+            year_to_date: self.year_to_date.convert(rates, to)?,
+        })
+    }
+}
+
+/// A row per month, per quarter and for the year to date.
+impl fmt::Display for PeriodSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Totals of All Statements ({})", self.currency)?;
+        write!(f, "{:<14}", "")?;
+        for heading in [
+            "Revenue",
+            "Expenses",
+            "Net Income",
+            "Contributions",
+            "Draws",
+        ] {
+            write!(f, "{:>14}", heading)?;
+        }
+        writeln!(f)?;
+        for totals in self
+            .months
+            .iter()
+            .chain(&self.quarters)
+            .chain([&self.year_to_date])
+        {
+            write!(f, "{:<14}", totals.period.label)?;
+            for amount in totals.amounts() {
+                write!(f, "{:>14}", format!("{:.2}", amount.amount))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(
+            f,
+            "Year to date is {} through {}",
+            self.year_to_date.period.start, self.year_to_date.period.end
+        )
+    }
+}
+
+/// One account's balance on a balance sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BalanceLine {
+    pub account: String,
+    /// Positive when the account adds to its section, negative for contra accounts such
+    /// as owner's draws.
+    pub balance: Money,
+}
+
+/// Assets, liabilities and equity at the end of one day.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BalanceSheet {
+    pub currency: Currency,
+    pub date: NaiveDate,
+    pub assets: Vec<BalanceLine>,
+    pub total_assets: Money,
+    pub liabilities: Vec<BalanceLine>,
+    pub total_liabilities: Money,
+    /// The equity accounts followed by retained earnings, the net income of every entry
+    /// up to the date.
+    pub equity: Vec<BalanceLine>,
+    pub total_equity: Money,
+}
+
+/// The name of the balance sheet line holding accumulated net income.
+pub(crate) const RETAINED_EARNINGS: &str = "Retained Earnings";
+
+impl BalanceSheet {
+    pub fn from_ledger(ledger: &Ledger, date: NaiveDate) -> Self {
+        let lines = |account_type: AccountType| -> Vec<BalanceLine> {
+            ledger
+                .account_balances_between(account_type, NaiveDate::MIN, date)
+                .into_iter()
+                .filter(|(_, balance)| !balance.is_zero())
+                .map(|(account, balance)| BalanceLine {
+                    account: account.name.clone(),
+                    balance: if account.normal_side == account_type.normal_side() {
+                        balance
+                    } else {
+                        -balance
+                    },
+                })
+                .collect()
+        };
+        let mut equity = lines(AccountType::Equity);
+        let retained_earnings = Money::new(
+            ledger.total_as_of(AccountType::Revenue, date).amount
+                - ledger.total_as_of(AccountType::Expense, date).amount,
+            ledger.currency,
+        );
+        equity.push(BalanceLine {
+            account: String::from(RETAINED_EARNINGS),
+            balance: retained_earnings,
+        });
+        let total_equity = Money::new(
+            ledger.total_as_of(AccountType::Equity, date).amount + retained_earnings.amount,
+            ledger.currency,
+        );
+        Self {
+            currency: ledger.currency,
+            date,
+            assets: lines(AccountType::Asset),
+            total_assets: ledger.total_as_of(AccountType::Asset, date),
+            liabilities: lines(AccountType::Liability),
+            total_liabilities: ledger.total_as_of(AccountType::Liability, date),
+            equity,
+            total_equity,
+        }
+    }
+
+    /// Assets less liabilities and equity, zero when the books balance.
+    pub fn difference(&self) -> Money {
+        Money::new(
+            self.total_assets.amount - self.total_liabilities.amount - self.total_equity.amount,
+            self.currency,
+        )
+    }
+}
+
+impl fmt::Display for BalanceSheet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Balance Sheet as of {} ({})", self.date, self.currency)?;
+        for (heading, lines, total_label, total) in [
+            ("Assets", &self.assets, "Total Assets", self.total_assets),
+            (
+                "Liabilities",
+                &self.liabilities,
+                "Total Liabilities",
+                self.total_liabilities,
+            ),
+            ("Equity", &self.equity, "Total Equity", self.total_equity),
+        ] {
+            writeln!(f, "{}", heading)?;
+            for line in lines {
+                writeln!(
+                    f,
+                    "  {:<28}{:>13}",
+                    line.account,
+                    format!("{:.2}", line.balance.amount)
+                )?;
+            }
+            writeln!(
+                f,
+                "{:<30}{:>13}",
+                total_label,
+                format!("{:.2}", total.amount)
+            )?;
+        }
+        let liabilities_and_equity = self.total_liabilities.amount + self.total_equity.amount;
+        writeln!(
+            f,
+            "{:<30}{:>13}",
+            "Total Liabilities and Equity",
+            format!("{:.2}", liabilities_and_equity)
+        )?;
+        if self.difference().is_zero() {
+            writeln!(f, "Assets equal liabilities plus equity")
+        } else {
+            writeln!(
+                f,
+                "Assets differ from liabilities plus equity by {:.2}",
+                self.difference()
+            )
+        }
+    }
+}
+
+/// Total equity, retained earnings included, at the end of `date`.
+fn equity_as_of(ledger: &Ledger, date: NaiveDate) -> Money {
+    Money::new(
+        ledger.total_as_of(AccountType::Equity, date).amount
+            + ledger.total_as_of(AccountType::Revenue, date).amount
+            - ledger.total_as_of(AccountType::Expense, date).amount,
+        ledger.currency,
+    )
+}
+
+/// How the owner's equity moved in each column of a date range: opening equity, plus
+/// contributions and net income, less draws and unclassified transfers, is the closing
+/// equity.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EquityRollforward {
+    pub currency: Currency,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub periods: Vec<ReportPeriod>,
+    pub opening_equity: Vec<Money>,
+    pub contributions: Vec<Money>,
+    pub net_income: Vec<Money>,
+    /// Positive amounts, which reduce equity.
+    pub draws: Vec<Money>,
+    /// Transfers out no rule classified yet, positive amounts like draws.
+    pub unclassified_transfers: Vec<Money>,
+    /// Any other equity accounts booked within the column, such as opening balances dated
+    /// after its first day.
+    pub other_changes: Vec<Money>,
+    pub closing_equity: Vec<Money>,
+}
+
+impl EquityRollforward {
+    pub fn from_ledger(
+        ledger: &Ledger,
+        start: NaiveDate,
+        end: NaiveDate,
+        columns: ReportColumns,
+        fiscal_year: FiscalYear,
+    ) -> Result<Self, LedgerError> {
+        let currency = ledger.currency;
+        let periods = report_periods(start, end, columns, fiscal_year);
+        let mut rollforward = Self {
+            currency,
+            start,
+            end,
+            periods: vec![],
+            opening_equity: vec![],
+            contributions: vec![],
+            net_income: vec![],
+            draws: vec![],
+            unclassified_transfers: vec![],
+            other_changes: vec![],
+            closing_equity: vec![],
+        };
+        for period in &periods {
+            let before_start = match period.start.pred_opt() {
+                Some(day_before) => equity_as_of(ledger, day_before),
+                None => Money::zero(currency),
+            };
+            // Opening balances are booked on the first day they apply from, so those dated
+            // on the column's first day are part of its opening equity.
+            let opened_on_start =
+                ledger.balance_between(OPENING_BALANCE_EQUITY, period.start, period.start)?;
+            let opening = Money::new(before_start.amount + opened_on_start.amount, currency);
+            let contributions =
+                ledger.balance_between(OWNER_CONTRIBUTIONS, period.start, period.end)?;
+            let draws = ledger.balance_between(OWNER_DRAWS, period.start, period.end)?;
+            let unclassified_transfers =
+                ledger.balance_between(UNCLASSIFIED_TRANSFERS, period.start, period.end)?;
+            let net_income = Money::new(
+                ledger
+                    .total_between(AccountType::Revenue, period.start, period.end)
+                    .amount
+                    - ledger
+                        .total_between(AccountType::Expense, period.start, period.end)
+                        .amount,
+                currency,
+            );
+            let closing = equity_as_of(ledger, period.end);
+            let other_changes = Money::new(
+                closing.amount - opening.amount - contributions.amount - net_income.amount
+                    + draws.amount
+                    + unclassified_transfers.amount,
+                currency,
+            );
+            rollforward.opening_equity.push(opening);
+            rollforward.contributions.push(contributions);
+            rollforward.net_income.push(net_income);
+            rollforward.draws.push(draws);
+            rollforward
+                .unclassified_transfers
+                .push(unclassified_transfers);
+            rollforward.other_changes.push(other_changes);
+            rollforward.closing_equity.push(closing);
+        }
+        rollforward.periods = periods;
+        Ok(rollforward)
+    }
+}
+
+/// A table with a column per period, draws and unclassified transfers shown as negative
+/// amounts.
+impl fmt::Display for EquityRollforward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Equity Rollforward {} through {} ({})",
+            self.start, self.end, self.currency
+        )?;
+        write!(f, "{:<26}", "")?;
+        for period in &self.periods {
+            write!(f, "{:>13}", period.label)?;
+        }
+        writeln!(f)?;
+        // Subtracted from zero rather than negated, so no draws print as 0.00, not -0.00.
+        let reductions = |amounts: &[Money]| -> Vec<Money> {
+            amounts
+                .iter()
+                .map(|amount| {
+                    Money::new(
+                        Money::zero(self.currency).amount - amount.amount,
+                        self.currency,
+                    )
+                })
+                .collect()
+        };
+        let draws = reductions(&self.draws);
+        let unclassified_transfers = reductions(&self.unclassified_transfers);
+        for (label, amounts) in [
+            ("Opening Equity", &self.opening_equity),
+            ("Contributions", &self.contributions),
+            ("Net Income", &self.net_income),
+            ("Draws", &draws),
+            ("Unclassified Transfers", &unclassified_transfers),
+            ("Other Changes", &self.other_changes),
+            ("Closing Equity", &self.closing_equity),
+        ] {
+            write!(f, "{:<26}", label)?;
+            for amount in amounts {
+                write!(f, "{:>13}", format!("{:.2}", amount.amount))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The activities a cash flow statement groups cash receipts and payments into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CashFlowActivity {
+    Operating,
+    Investing,
+    Financing,
+}
+
+impl CashFlowActivity {
+    /// The activity of cash moving to or from an account of `account_type`: revenue and
+    /// expenses are operating, other assets such as equipment are investing, and loans,
+    /// owner's contributions, owner's draws and unclassified transfers are financing.
+    pub fn of(account_type: AccountType) -> Self {
+        match account_type {
+            AccountType::Revenue | AccountType::Expense => CashFlowActivity::Operating,
+            AccountType::Asset => CashFlowActivity::Investing,
+            AccountType::Liability | AccountType::Equity => CashFlowActivity::Financing,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CashFlowActivity::Operating => "Operating",
+            CashFlowActivity::Investing => "Investing",
+            CashFlowActivity::Financing => "Financing",
+        }
+    }
+}
+
+/// The cash received from or paid to one account, positive for receipts.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CashFlowLine {
+    pub account: String,
+    pub amount: Money,
+}
+
+/// A direct-method statement of cash flows: every receipt into and payment out of the bank
+/// account, by the account on the other side.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CashFlowStatement {
+    pub currency: Currency,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// The bank balance before `start`, with any opening balance booked within the range.
+    pub beginning_cash: Money,
+    /// The lines of each activity, in the order the accounts first move cash.
+    pub activities: Vec<(CashFlowActivity, Vec<CashFlowLine>, Money)>,
+    pub net_change: Money,
+    /// The bank balance at the end of `end`.
+    pub ending_cash: Money,
+}
+
+impl CashFlowStatement {
+    pub fn from_ledger(
+        ledger: &Ledger,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Self, LedgerError> {
+        let currency = ledger.currency;
+        let mut beginning_cash = match start.pred_opt() {
+            Some(day_before) => ledger.balance_between(BANK_ACCOUNT, NaiveDate::MIN, day_before)?,
+            None => Money::zero(currency),
+        };
+        let mut activities: Vec<(CashFlowActivity, Vec<CashFlowLine>, Money)> = [
+            CashFlowActivity::Operating,
+            CashFlowActivity::Investing,
+            CashFlowActivity::Financing,
+        ]
+        .into_iter()
+        .map(|activity| (activity, vec![], Money::zero(currency)))
+        .collect();
+        for entry in ledger
+            .entries
+            .iter()
+            .filter(|entry| (start..=end).contains(&entry.date))
+            .filter(|entry| entry.postings.iter().any(|p| p.account == BANK_ACCOUNT))
+        {
+            for posting in entry.postings.iter().filter(|p| p.account != BANK_ACCOUNT) {
+                // Cash comes in when the other side is credited.
+                let amount = match posting.side {
+                    EntrySide::Credit => posting.amount,
+                    EntrySide::Debit => -posting.amount,
+                };
+                if posting.account == OPENING_BALANCE_EQUITY {
+                    beginning_cash = Money::new(beginning_cash.amount + amount.amount, currency);
+                    continue;
+                }
+                let account = ledger.chart.get(&posting.account).ok_or_else(|| {
+                    LedgerError::UnknownAccount {
+                        name: posting.account.clone(),
+                    }
+                })?;
+                let activity = CashFlowActivity::of(account.account_type);
+                let (_, lines, net) = activities
+                    .iter_mut()
+                    .find(|(a, _, _)| *a == activity)
+                    .unwrap();
+                match lines
+                    .iter_mut()
+                    .find(|line| line.account == posting.account)
+                {
+                    Some(line) => {
+                        line.amount = Money::new(line.amount.amount + amount.amount, currency)
+                    }
+                    None => lines.push(CashFlowLine {
+                        account: posting.account.clone(),
+                        amount,
+                    }),
+                }
+                *net = Money::new(net.amount + amount.amount, currency);
+            }
+        }
+        let net_change = Money::total(currency, activities.iter().map(|(_, _, net)| *net))
+            .expect("cash flows are in the ledger currency");
+        Ok(Self {
+            currency,
+            start,
+            end,
+            beginning_cash,
+            activities,
+            net_change,
+            ending_cash: ledger.balance_between(BANK_ACCOUNT, NaiveDate::MIN, end)?,
+        })
+    }
+
+    /// The ending cash less the beginning cash and the net change, zero when every movement
+    /// of the bank account is accounted for.
+    pub fn difference(&self) -> Money {
+        Money::new(
+            self.ending_cash.amount - self.beginning_cash.amount - self.net_change.amount,
+            self.currency,
+        )
+    }
+}
+
+impl fmt::Display for CashFlowStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Statement of Cash Flows {} through {} ({})",
+            self.start, self.end, self.currency
+        )?;
+        for (activity, lines, net) in &self.activities {
+            writeln!(f, "{} Activities", activity.name())?;
+            for line in lines {
+                writeln!(
+                    f,
+                    "  {:<28}{:>13}",
+                    line.account,
+                    format!("{:.2}", line.amount.amount)
+                )?;
+            }
+            writeln!(
+                f,
+                "{:<30}{:>13}",
+                format!("Net Cash from {}", activity.name()),
+                format!("{:.2}", net.amount)
+            )?;
+        }
+        for (label, amount) in [
+            ("Net Change in Cash", self.net_change),
+            ("Cash at Beginning", self.beginning_cash),
+            ("Cash at End", self.ending_cash),
+        ] {
+            writeln!(f, "{:<30}{:>13}", label, format!("{:.2}", amount.amount))?;
+        }
+        if self.difference().is_zero() {
+            writeln!(f, "Cash flows reconcile to the net change in cash")
+        } else {
+            writeln!(
+                f,
+                "Cash flows differ from the net change in cash by {:.2}",
+                self.difference()
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger_utils::{
+        parse_opening_balances, ChartOfAccounts, JournalEntry, BANK_ACCOUNT, BANK_FEES,
+        CARD_PURCHASES, LOANS_PAYABLE, OPENING_BALANCE_EQUITY, OWNER_CONTRIBUTIONS, OWNER_DRAWS,
+        SALES_REVENUE,
+    };
+    use fastnum::decimal::Context;
+    use fastnum::D256;
+
+    fn usd(amount: &str) -> Money {
+        Money::new(
+            D256::from_str(amount, Context::default()).unwrap(),
+            Currency::Usd,
+        )
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn splits_range_into_calendar_columns() {
+        let months = report_periods(
+            date(1, 15),
+            date(3, 10),
+            ReportColumns::Month,
+            FiscalYear::default(),
+        );
+        assert_eq!(
+            months
+                .iter()
+                .map(|period| (period.label.as_str(), period.start, period.end))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2024-01", date(1, 15), date(1, 31)),
+                ("2024-02", date(2, 1), date(2, 29)),
+                ("2024-03", date(3, 1), date(3, 10)),
+            ]
+        );
+        let quarters = report_periods(
+            date(2, 1),
+            date(7, 31),
+            ReportColumns::Quarter,
+            FiscalYear::default(),
+        );
+        assert_eq!(
+            quarters
+                .iter()
+                .map(|period| period.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["2024 Q1", "2024 Q2", "2024 Q3"]
+        );
+        assert_eq!(quarters[0].start, date(2, 1));
+        assert_eq!(quarters[2].end, date(7, 31));
+    }
+
+    #[test]
+    fn combines_totals_by_month_fiscal_quarter_and_year_to_date() {
+        let july_fiscal_year = FiscalYear { start_month: 7 };
+        assert_eq!(
+            july_fiscal_year.start_of(date(3, 10)),
+            NaiveDate::from_ymd_opt(2023, 7, 1).unwrap()
+        );
+        assert_eq!(july_fiscal_year.name_of(date(3, 10)), "FY2024");
+        assert_eq!(july_fiscal_year.name_of(date(7, 1)), "FY2025");
+        assert_eq!(july_fiscal_year.quarter_of(date(3, 10)), 3);
+        assert_eq!(FiscalYear::default().name_of(date(3, 10)), "2024");
+
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        for (day, debit, credit, amount) in [
+            (date(3, 10), BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            (date(3, 20), CARD_PURCHASES, BANK_ACCOUNT, "200"),
+            (date(4, 5), BANK_ACCOUNT, SALES_REVENUE, "500"),
+            (date(6, 30), OWNER_DRAWS, BANK_ACCOUNT, "300"),
+            (date(7, 2), BANK_ACCOUNT, OWNER_CONTRIBUTIONS, "50"),
+            (date(7, 3), BANK_FEES, BANK_ACCOUNT, "15"),
+        ] {
+            ledger
+                .post(JournalEntry::transfer(
+                    day,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+
+        let summary =
+            PeriodSummary::from_ledger(&ledger, date(3, 1), date(7, 31), july_fiscal_year).unwrap();
+        assert_eq!(summary.months.len(), 5);
+        assert_eq!(summary.months[1].net_income, usd("500"));
+        assert_eq!(
+            summary
+                .quarters
+                .iter()
+                .map(|totals| (totals.period.label.as_str(), totals.net_income))
+                .collect::<Vec<_>>(),
+            vec![
+                ("FY2024 Q3", usd("800")),
+                ("FY2024 Q4", usd("500")),
+                ("FY2025 Q1", usd("-15")),
+            ]
+        );
+        assert_eq!(summary.quarters[1].draws, usd("300"));
+        let year_to_date = &summary.year_to_date;
+        assert_eq!(year_to_date.period.label, "FY2025 YTD");
+        assert_eq!(year_to_date.period.start, date(7, 1));
+        assert_eq!(year_to_date.contributions, usd("50"));
+        assert_eq!(year_to_date.expenses, usd("15"));
+    }
+
+    #[test]
+    fn totals_categories_per_column() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        for (day, debit, credit, amount) in [
+            (date(1, 10), BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            (date(1, 20), CARD_PURCHASES, BANK_ACCOUNT, "200"),
+            (date(2, 5), BANK_ACCOUNT, SALES_REVENUE, "500"),
+            (date(4, 30), BANK_FEES, BANK_ACCOUNT, "15"),
+        ] {
+            ledger
+                .post(JournalEntry::transfer(
+                    day,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+
+        let quarterly = IncomeStatement::from_ledger(
+            &ledger,
+            date(1, 1),
+            date(6, 30),
+            ReportColumns::Quarter,
+            FiscalYear::default(),
+        );
+        assert_eq!(quarterly.revenue.len(), 1);
+        assert_eq!(quarterly.revenue[0].amounts, vec![usd("1500"), usd("0")]);
+        assert_eq!(
+            quarterly
+                .expenses
+                .iter()
+                .map(|line| line.account.as_str())
+                .collect::<Vec<_>>(),
+            vec![CARD_PURCHASES, BANK_FEES]
+        );
+        assert_eq!(quarterly.total_expenses, vec![usd("200"), usd("15")]);
+        assert_eq!(quarterly.net_income, vec![usd("1300"), usd("-15")]);
+        assert!(quarterly
+            .to_csv()
+            .starts_with("line,2024 Q1,2024 Q2\nRevenue\nSales Revenue,1500.00,0.00\n"));
+
+        let february = IncomeStatement::from_ledger(
+            &ledger,
+            date(2, 1),
+            date(2, 29),
+            ReportColumns::Total,
+            FiscalYear::default(),
+        );
+        assert_eq!(february.expenses, vec![]);
+        assert_eq!(february.net_income, vec![usd("500")]);
+        assert!(february.to_string().contains("Net Income"));
+    }
+
+    #[test]
+    fn balance_sheet_balances_with_liabilities_draws_and_retained_earnings() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        ledger.post_opening_balance(date(1, 1), usd("500")).unwrap();
+        let opening_balances =
+            parse_opening_balances("# account,type,amount\nLoans Payable,liability,2000.00\n")
+                .unwrap();
+        ledger
+            .post_opening_balances(date(1, 1), &opening_balances)
+            .unwrap();
+        for (day, debit, credit, amount) in [
+            (date(1, 10), BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            (date(1, 20), CARD_PURCHASES, BANK_ACCOUNT, "200"),
+            (date(1, 25), OWNER_DRAWS, BANK_ACCOUNT, "300"),
+            (date(2, 1), LOANS_PAYABLE, BANK_ACCOUNT, "100"),
+            (date(2, 5), BANK_ACCOUNT, SALES_REVENUE, "500"),
+        ] {
+            ledger
+                .post(JournalEntry::transfer(
+                    day,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+
+        let january = BalanceSheet::from_ledger(&ledger, date(1, 31));
+        assert_eq!(january.total_assets, usd("1000"));
+        assert_eq!(
+            january.liabilities,
+            vec![BalanceLine {
+                account: String::from(LOANS_PAYABLE),
+                balance: usd("2000")
+            }]
+        );
+        assert_eq!(
+            january
+                .equity
+                .iter()
+                .map(|line| (line.account.as_str(), line.balance))
+                .collect::<Vec<_>>(),
+            vec![
+                (OPENING_BALANCE_EQUITY, usd("-1500")),
+                (OWNER_DRAWS, usd("-300")),
+                (RETAINED_EARNINGS, usd("800")),
+            ]
+        );
+        assert_eq!(january.total_equity, usd("-1000"));
+        assert!(january.difference().is_zero());
+
+        let february = BalanceSheet::from_ledger(&ledger, date(2, 29));
+        assert_eq!(february.total_assets, usd("1400"));
+        assert_eq!(february.total_liabilities, usd("1900"));
+        assert!(february.difference().is_zero());
+        assert!(february
+            .to_string()
+            .contains("Assets equal liabilities plus equity"));
+    }
+
+    #[test]
+    fn equity_rolls_forward_from_month_to_month() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        ledger.post_opening_balance(date(1, 1), usd("500")).unwrap();
+        for (day, debit, credit, amount) in [
+            (date(1, 10), BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            (date(1, 20), CARD_PURCHASES, BANK_ACCOUNT, "200"),
+            (date(1, 25), OWNER_DRAWS, BANK_ACCOUNT, "300"),
+            (date(2, 3), BANK_ACCOUNT, OWNER_CONTRIBUTIONS, "2000"),
+            (date(2, 5), BANK_ACCOUNT, SALES_REVENUE, "500"),
+            (date(2, 7), UNCLASSIFIED_TRANSFERS, BANK_ACCOUNT, "100"),
+        ] {
+            ledger
+                .post(JournalEntry::transfer(
+                    day,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+
+        let rollforward = EquityRollforward::from_ledger(
+            &ledger,
+            date(1, 1),
+            date(2, 29),
+            ReportColumns::Month,
+            FiscalYear::default(),
+        )
+        .unwrap();
+        assert_eq!(rollforward.opening_equity, vec![usd("500"), usd("1000")]);
+        assert_eq!(rollforward.contributions, vec![usd("0"), usd("2000")]);
+        assert_eq!(rollforward.net_income, vec![usd("800"), usd("500")]);
+        assert_eq!(rollforward.draws, vec![usd("300"), usd("0")]);
+        assert_eq!(
+            rollforward.unclassified_transfers,
+            vec![usd("0"), usd("100")]
+        );
+        assert_eq!(rollforward.other_changes, vec![usd("0"), usd("0")]);
+        assert_eq!(rollforward.closing_equity, vec![usd("1000"), usd("3400")]);
+        assert_eq!(
+            rollforward.closing_equity[1],
+            BalanceSheet::from_ledger(&ledger, date(2, 29)).total_equity
+        );
+        assert!(rollforward
+            .to_string()
+            .contains("Draws                           -300.00         0.00"));
+    }
+
+    #[test]
+    fn cash_flows_by_activity_reconcile_to_the_change_in_cash() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        ledger.post_opening_balance(date(1, 1), usd("500")).unwrap();
+        ledger
+            .post_opening_balances(
+                date(1, 1),
+                &parse_opening_balances("Delivery Van,asset,0\n").unwrap(),
+            )
+            .unwrap();
+        for (day, debit, credit, amount) in [
+            (date(1, 10), BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            (date(1, 12), BANK_ACCOUNT, LOANS_PAYABLE, "5000"),
+            (date(1, 13), "Delivery Van", BANK_ACCOUNT, "4500"),
+            (date(1, 20), CARD_PURCHASES, BANK_ACCOUNT, "200"),
+            (date(1, 25), OWNER_DRAWS, BANK_ACCOUNT, "300"),
+            (date(2, 5), BANK_ACCOUNT, SALES_REVENUE, "500"),
+        ] {
+            ledger
+                .post(JournalEntry::transfer(
+                    day,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+
+        let january = CashFlowStatement::from_ledger(&ledger, date(1, 1), date(1, 31)).unwrap();
+        assert_eq!(january.beginning_cash, usd("500"));
+        let nets: Vec<(CashFlowActivity, Money)> = january
+            .activities
+            .iter()
+            .map(|(activity, _, net)| (*activity, *net))
+            .collect();
+        assert_eq!(
+            nets,
+            vec![
+                (CashFlowActivity::Operating, usd("800")),
+                (CashFlowActivity::Investing, usd("-4500")),
+                (CashFlowActivity::Financing, usd("4700")),
+            ]
+        );
+        assert_eq!(
+            january.activities[2].1,
+            vec![
+                CashFlowLine {
+                    account: String::from(LOANS_PAYABLE),
+                    amount: usd("5000")
+                },
+                CashFlowLine {
+                    account: String::from(OWNER_DRAWS),
+                    amount: usd("-300")
+                },
+            ]
+        );
+        assert_eq!(january.net_change, usd("1000"));
+        assert_eq!(january.ending_cash, usd("1500"));
+        assert!(january.difference().is_zero());
+
+        let february = CashFlowStatement::from_ledger(&ledger, date(2, 1), date(2, 29)).unwrap();
+        assert_eq!(february.beginning_cash, usd("1500"));
+        assert_eq!(february.net_change, usd("500"));
+        assert!(february
+            .to_string()
+            .contains("Cash flows reconcile to the net change in cash"));
+    }
+
+    #[test]
+    fn unclassified_transfers_are_financing_and_held_in_equity() {
+        let mut ledger = Ledger::new(ChartOfAccounts::default(), Currency::Usd);
+        ledger.post_opening_balance(date(1, 1), usd("500")).unwrap();
+        for (day, debit, credit, amount) in [
+            (date(1, 10), BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            (date(1, 25), UNCLASSIFIED_TRANSFERS, BANK_ACCOUNT, "300"),
+        ] {
+            ledger
+                .post(JournalEntry::transfer(
+                    day,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+
+        let cash_flows = CashFlowStatement::from_ledger(&ledger, date(1, 1), date(1, 31)).unwrap();
+        let (_, investing, investing_net) = &cash_flows.activities[1];
+        assert!(investing.is_empty());
+        assert!(investing_net.is_zero());
+        let (activity, financing, financing_net) = &cash_flows.activities[2];
+        assert_eq!(*activity, CashFlowActivity::Financing);
+        assert_eq!(
+            *financing,
+            vec![CashFlowLine {
+                account: String::from(UNCLASSIFIED_TRANSFERS),
+                amount: usd("-300")
+            }]
+        );
+        assert_eq!(*financing_net, usd("-300"));
+        assert!(cash_flows.difference().is_zero());
+
+        let balance_sheet = BalanceSheet::from_ledger(&ledger, date(1, 31));
+        assert_eq!(balance_sheet.total_assets, usd("1200"));
+        assert!(balance_sheet
+            .assets
+            .iter()
+            .all(|line| line.account != UNCLASSIFIED_TRANSFERS));
+        assert!(balance_sheet.equity.contains(&BalanceLine {
+            account: String::from(UNCLASSIFIED_TRANSFERS),
+            balance: usd("-300"),
+        }));
+        assert!(balance_sheet.difference().is_zero());
+    }
+}
@@ -0,0 +1,490 @@
+use crate::balance_utils::DailyBalance;
+use crate::errors::ParseError;
+use crate::money_utils::{amount_pattern, parse_money_token, Money};
+use crate::page_utils::{PageMarkers, StitchedStatement};
+use crate::parse_utils::{
+    parse_transaction_date, subtype_rules, ExtractedTransactions, SubtypeRule, TransactionKind,
+    TransactionSubtype,
+};
+use crate::period_utils::{period_pattern, StatementPeriod};
+use crate::profile::{labels_pattern, ParsingProfile};
+use crate::provenance::Provenance;
+use crate::section_utils::{heading_pattern, StatementSection};
+use crate::summary_utils::summary_total_pattern;
+use regex::Regex;
+
+/// The text of one transaction entry within the stitched statement.
+#[derive(Debug, PartialEq)]
+struct ScannedEntry {
+    section: StatementSection,
+    start: usize,
+    end: usize,
+}
+
+/// Every regex used to read a statement, compiled once for a parsing profile and reused
+/// for all statements parsed with it.
+pub(crate) struct StatementScanner {
+    pub profile: ParsingProfile,
+    pub period_re: Regex,
+    pub account_number_re: Regex,
+    pub begin_balance_re: Regex,
+    pub end_balance_re: Regex,
+    pub summary_total_re: Regex,
+    pub page_markers: PageMarkers,
+    heading_re: Regex,
+    entry_start_re: Regex,
+    section_total_re: Regex,
+    credit_rules: Vec<SubtypeRule>,
+    debit_rules: Vec<SubtypeRule>,
+    default_entry_re: Regex,
+    daily_balance_re: Regex,
+}
+
+impl StatementScanner {
+    pub fn new(profile: ParsingProfile) -> Self {
+        let locale = &profile.locale;
+        let date = locale.date_pattern();
+        let amount = amount_pattern(locale);
+        let (credit_rules, debit_rules) = subtype_rules(locale)
+            .into_iter()
+            .partition(|rule| rule.subtype.debit_or_credit() == TransactionKind::Credit);
+        let labels = &locale.labels;
+        Self {
+            period_re: Regex::new(&period_pattern(locale)).unwrap(),
+            account_number_re: Regex::new(&format!(
+                r"(?m)^[ \t]*(?:{}):?[ \t]*(?<number>\d[\d-]*\d)[ \t]*$",
+                labels_pattern(&labels.account_number)
+            ))
+            .unwrap(),
+            begin_balance_re: Regex::new(&format!(
+                r"(?m)^{}\s.*?(?<amount>{amount})\s*$",
+                labels_pattern(&[&labels.beginning_balance])
+            ))
+            .unwrap(),
+            end_balance_re: Regex::new(&format!(
+                r"(?m)^{}\s.*?(?<amount>{amount})\s*$",
+                labels_pattern(&[&labels.ending_balance])
+            ))
+            .unwrap(),
+            summary_total_re: Regex::new(&summary_total_pattern(locale, &amount)).unwrap(),
+            page_markers: PageMarkers::new(locale),
+            heading_re: Regex::new(&heading_pattern(locale)).unwrap(),
+            entry_start_re: Regex::new(&format!(r"^[ \t]*{date}\s")).unwrap(),
+            section_total_re: Regex::new(&format!(
+                r"^[ \t]*{}\s",
+                regex::escape(&labels.section_total)
+            ))
+            .unwrap(),
+            credit_rules,
+            debit_rules,
+            default_entry_re: Regex::new(&format!(
+                r"(?ms)(?<date>{date})\s(.+?)(?<amount>{amount}).?$"
+            ))
+            .unwrap(),
+            daily_balance_re: Regex::new(&format!(r"(?<date>{date})[ \t]+(?<amount>{amount})"))
+                .unwrap(),
+            profile,
+        }
+    }
+
+    /// Splits the stitched text into transaction entries in a single pass over its lines.
+    ///
+    /// Each line is either a section heading, the start of an entry (a line beginning with
+    /// a date), a `Total ...` line closing the section, or a continuation of the open entry.
+    /// Under the daily ending balance heading, every line with a date is an entry of its own.
+    /// A line with a date and an amount under an unrecognized heading, or between a section's
+    /// total and the next heading, belongs to no section and is reported in `warnings`.
+    fn scan_entries(
+        &self,
+        stitched: &StitchedStatement,
+        warnings: &mut Vec<ParseError>,
+    ) -> Vec<ScannedEntry> {
+        let statement_str = stitched.text.as_str();
+        let mut entries: Vec<ScannedEntry> = vec![];
+        let mut current_section: Option<StatementSection> = None;
+        let mut open_entry: Option<ScannedEntry> = None;
+        let mut line_start: usize = 0;
+
+        for line in statement_str.split_inclusive('\n') {
+            let line_end = line_start + line.len();
+            let content = line.trim_end_matches(['\r', '\n']);
+            if let Some(heading_captures) = self.heading_re.captures(content) {
+                entries.extend(open_entry.take());
+                current_section = self
+                    .profile
+                    .locale
+                    .labels
+                    .section_for_heading(&heading_captures["heading"]);
+            } else if current_section.is_some_and(|section| section.direction().is_some()) {
+                if self.section_total_re.is_match(content) {
+                    entries.extend(open_entry.take());
+                    current_section = None;
+                } else if self.entry_start_re.is_match(content) {
+                    entries.extend(open_entry.take());
+                    open_entry = current_section.map(|section| ScannedEntry {
+                        section,
+                        start: line_start,
+                        end: line_end,
+                    });
+                } else if let Some(entry) = open_entry.as_mut() {
+                    entry.end = line_end;
+                }
+            } else if current_section == Some(StatementSection::DailyEndingBalance)
+                && self.daily_balance_re.is_match(content)
+            {
+                entries.push(ScannedEntry {
+                    section: StatementSection::DailyEndingBalance,
+                    start: line_start,
+                    end: line_end,
+                });
+            } else if current_section.is_none()
+                && self.entry_start_re.is_match(content)
+                && self.default_entry_re.is_match(content)
+            {
+                warnings.push(ParseError::UnmatchedEntry {
+                    location: stitched.location(line_start),
+                    text: String::from(content.trim_end()),
+                    section: None,
+                });
+            }
+            line_start = line_end;
+        }
+        entries.extend(open_entry);
+        entries
+    }
+
+    /// Extracts every transaction listed under a section heading of the stitched statement,
+    /// and every date and balance pair of its daily ending balance table, from a single
+    /// scan of its lines.
+    ///
+    /// An entry whose date or amount cannot be parsed is skipped and reported in `warnings`,
+    /// so one malformed line does not stop the rest of the statement from being read.
+    pub fn extract_entries(
+        &self,
+        stitched: &StitchedStatement,
+        period: &StatementPeriod,
+        warnings: &mut Vec<ParseError>,
+    ) -> (ExtractedTransactions, Vec<DailyBalance>) {
+        let mut transactions = ExtractedTransactions::default();
+        let mut daily_balances: Vec<DailyBalance> = vec![];
+        for scanned_entry in self.scan_entries(stitched, warnings) {
+            if scanned_entry.section == StatementSection::DailyEndingBalance {
+                self.push_daily_balances(
+                    stitched,
+                    &scanned_entry,
+                    period,
+                    &mut daily_balances,
+                    warnings,
+                );
+            } else {
+                self.push_transaction(
+                    stitched,
+                    &scanned_entry,
+                    period,
+                    &mut transactions,
+                    warnings,
+                );
+            }
+        }
+        (transactions, daily_balances)
+    }
+
+    /// Reads one entry listed under a transaction section.
+    ///
+    /// The section decides whether an entry is a debit or a credit; the keyword rules only
+    /// pick the subtype within that direction, falling back to the section's default subtype.
+    fn push_transaction(
+        &self,
+        stitched: &StitchedStatement,
+        scanned_entry: &ScannedEntry,
+        period: &StatementPeriod,
+        transactions: &mut ExtractedTransactions,
+        warnings: &mut Vec<ParseError>,
+    ) {
+        let entry = &stitched.text[scanned_entry.start..scanned_entry.end];
+        let location = stitched.location(scanned_entry.start);
+        let (Some(direction), Some(default_subtype)) = (
+            scanned_entry.section.direction(),
+            TransactionSubtype::section_default(scanned_entry.section),
+        ) else {
+            return;
+        };
+        let rules = match direction {
+            TransactionKind::Credit => &self.credit_rules,
+            TransactionKind::Debit => &self.debit_rules,
+        };
+        let classified = rules
+            .iter()
+            .find_map(|rule| {
+                rule.re_expr
+                    .captures(entry)
+                    .map(|c| (rule.subtype, String::from(rule.name), c))
+            })
+            .or_else(|| {
+                self.default_entry_re.captures(entry).map(|c| {
+                    let rule = format!("{} default", scanned_entry.section.heading());
+                    (default_subtype, rule, c)
+                })
+            });
+        let Some((subtype, rule, captures)) = classified else {
+            warnings.push(ParseError::UnmatchedEntry {
+                location,
+                text: String::from(entry.trim_end()),
+                section: Some(scanned_entry.section),
+            });
+            return;
+        };
+        let matched = captures.get(0).expect("group 0 is the whole match");
+        let raw_text = matched.as_str().trim_end();
+        let matched_start = scanned_entry.start + matched.start();
+        let (start, end) = stitched.span(matched_start..matched_start + raw_text.len());
+        let transaction_date = match parse_transaction_date(
+            &captures["date"],
+            period,
+            &self.profile.locale,
+            start.clone(),
+        ) {
+            Ok(date) => date,
+            Err(e) => {
+                warnings.push(e);
+                return;
+            }
+        };
+        let source = Provenance {
+            start,
+            end,
+            raw_text: String::from(raw_text),
+            section: scanned_entry.section,
+            rule,
+        };
+        transactions.push(
+            subtype,
+            transaction_date,
+            &captures["amount"],
+            &self.profile,
+            source,
+            warnings,
+        );
+    }
+
+    /// Reads every date and balance pair of one line of the daily ending balance table,
+    /// which may list several pairs side by side.
+    fn push_daily_balances(
+        &self,
+        stitched: &StitchedStatement,
+        scanned_entry: &ScannedEntry,
+        period: &StatementPeriod,
+        daily_balances: &mut Vec<DailyBalance>,
+        warnings: &mut Vec<ParseError>,
+    ) {
+        let line = &stitched.text[scanned_entry.start..scanned_entry.end];
+        for captures in self.daily_balance_re.captures_iter(line) {
+            let date_match = captures.name("date").expect("group is not optional");
+            let amount_match = captures.name("amount").expect("group is not optional");
+            let date = match parse_transaction_date(
+                date_match.as_str(),
+                period,
+                &self.profile.locale,
+                stitched.location(scanned_entry.start + date_match.start()),
+            ) {
+                Ok(date) => date,
+                Err(e) => {
+                    warnings.push(e);
+                    continue;
+                }
+            };
+            let location = stitched.location(scanned_entry.start + amount_match.start());
+            match parse_money_token(amount_match.as_str(), &self.profile.locale) {
+                Ok(token) => daily_balances.push(DailyBalance {
+                    date,
+                    balance: Money::new(token.balance_amount(), self.profile.currency),
+                    location,
+                }),
+                Err(_) => warnings.push(ParseError::InvalidAmount {
+                    location,
+                    text: String::from(amount_match.as_str()),
+                }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page_utils::stitch_pages;
+    use chrono::NaiveDate;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    fn january_2024() -> StatementPeriod {
+        StatementPeriod {
+            start: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        }
+    }
+
+    fn sample_pages(entries_per_section: usize) -> Vec<String> {
+        let mut first_page = String::from(
+            "ACME LLC\nJanuary 1, 2024 through January 31, 2024\nCHECKING SUMMARY\nBeginning Balance $1,000.00\nDEPOSITS AND ADDITIONS\nDATE DESCRIPTION AMOUNT\n",
+        );
+        for i in 0..entries_per_section {
+            first_page.push_str(&format!(
+                "01/{:02} Orig CO Name:Client {} Orig ID:99 Descr:Payments Sec:CCD\nTrace#:{} $1,{:03}.00\n",
+                i % 28 + 1,
+                i,
+                i,
+                i % 1000
+            ));
+        }
+        first_page.push_str("Total Deposits and Additions $0.00\nELECTRONIC WITHDRAWALS\n");
+        for i in 0..entries_per_section {
+            first_page.push_str(&format!(
+                "01/{:02} Recurring Card Purchase 01/{:02} Tools {} Card 1234 {}.99\n",
+                i % 28 + 1,
+                i % 28 + 1,
+                i,
+                i % 100
+            ));
+            first_page.push_str(&format!(
+                "01/{:02} Vendor {} Bill Xfer Ref {} {}.50\n",
+                i % 28 + 1,
+                i,
+                i,
+                i % 100
+            ));
+        }
+        first_page.push_str("01/30 Online Transfer To Chk ...9876 Transaction#: 1\nPage 1 of 2\n");
+        let second_page = String::from(
+            "ACME LLC\nJanuary 1, 2024 through January 31, 2024\nELECTRONIC WITHDRAWALS (continued)\nDATE DESCRIPTION AMOUNT\n500.00\nTotal Electronic Withdrawals $0.00\nFEES\n01/31 Monthly Service Fee 15.00\nDAILY ENDING BALANCE\n01/31 1,000.00\nPage 2 of 2\n",
+        );
+        vec![first_page, second_page]
+    }
+
+    #[test]
+    fn classifies_entries_by_section_in_one_pass() {
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let pages = sample_pages(3);
+        let stitched = stitch_pages(Path::new("sample.pdf"), &pages, &scanner.page_markers);
+        let mut warnings = vec![];
+        let (transactions, daily_balances) =
+            scanner.extract_entries(&stitched, &january_2024(), &mut warnings);
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(daily_balances.len(), 1);
+        assert_eq!(transactions.deposits.len(), 3);
+        assert_eq!(transactions.card_purchases.len(), 3);
+        assert_eq!(transactions.payments.len(), 3);
+        assert_eq!(transactions.transfers_out.len(), 1);
+        assert_eq!(transactions.fees.len(), 1);
+        let wrapped_transfer = &transactions.transfers_out[0];
+        assert_eq!(wrapped_transfer.source.page(), 1);
+        assert_eq!(wrapped_transfer.source.end.page, 2);
+        assert_eq!(wrapped_transfer.source.rule, "online transfer out");
+        assert_eq!(
+            wrapped_transfer.source.raw_text,
+            "01/30 Online Transfer To Chk ...9876 Transaction#: 1\n500.00"
+        );
+        assert_eq!(format!("{:.2}", wrapped_transfer.amount), "500.00 USD");
+        let fee = &transactions.fees[0];
+        assert_eq!(fee.source.page(), 2);
+        assert_eq!(fee.source.rule, "FEES default");
+        let fee_offset = pages[1].find("01/31 Monthly").unwrap();
+        assert_eq!(fee.source.start.byte_offset, fee_offset);
+        assert_eq!(
+            &pages[1][fee.source.start.byte_offset..fee.source.end.byte_offset],
+            fee.source.raw_text
+        );
+    }
+
+    #[test]
+    fn warns_of_dated_amounts_under_no_section() {
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let page = String::from(
+            "DEPOSITS AND ADDITIONS\n01/05 Client A Deposit $1,000.00\nTotal Deposits and Additions $1,000.00\n01/06 Late Deposit $20.00\nCHECKS PAID\n01/07 Check 101 $50.00\nFEES\n01/31 Monthly Service Fee 15.00\n",
+        );
+        let stitched = stitch_pages(Path::new("sample.pdf"), &[page], &scanner.page_markers);
+        let mut warnings = vec![];
+        let (transactions, _) = scanner.extract_entries(&stitched, &january_2024(), &mut warnings);
+
+        assert_eq!(transactions.deposits.len(), 1);
+        assert_eq!(transactions.fees.len(), 1);
+        let unsectioned: Vec<&str> = warnings
+            .iter()
+            .map(|warning| match warning {
+                ParseError::UnmatchedEntry {
+                    text,
+                    section: None,
+                    ..
+                } => text.as_str(),
+                _ => panic!("{:?}", warning),
+            })
+            .collect();
+        assert_eq!(
+            unsectioned,
+            vec!["01/06 Late Deposit $20.00", "01/07 Check 101 $50.00"]
+        );
+    }
+
+    /// The extraction as it was before the scanner: every kind's regex compiled again for
+    /// each page, then restarted with `shortest_match_at` after each match.
+    fn extract_per_page_with_recompiled_regexes(page_str: &str) -> usize {
+        let patterns = [
+            r"(?ms)(?<date>\d{2}\/\d{2})\s(Orig\sCO\sName.+?)(Descr:Payments)(.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d)$",
+            r"(?ms)(?<date>\d{2}\/\d{2})\s(Recurring\sCard\sPurchase.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d)$",
+            r"(?ms)(?<date>\d{2}\/\d{2})\s(.+?)(Xfer)(.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d).?$",
+            r"(?ms)(?<date>\d{2}\/\d{2})\s(.+?)(Online\sTransfer\sTo)(.+?)[$]?(?<amount_with_commas>[\d+[,]]*\d.\d\d).?$",
+        ];
+        let mut found: usize = 0;
+        for pattern in patterns {
+            let re_expr = Regex::new(pattern).unwrap();
+            let mut start_byte_offset: usize = 0;
+            while let Some(end_byte_offset) = re_expr.shortest_match_at(page_str, start_byte_offset)
+            {
+                if re_expr
+                    .captures(&page_str[start_byte_offset..end_byte_offset])
+                    .is_some()
+                {
+                    found += 1;
+                }
+                start_byte_offset = end_byte_offset + 1;
+            }
+        }
+        found
+    }
+
+    /// Run with `cargo test --release scanner_benchmark -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn scanner_benchmark_beats_per_page_recompiling() {
+        let statements: Vec<Vec<String>> = (0..200).map(|_| sample_pages(40)).collect();
+
+        let started = Instant::now();
+        let mut legacy_found: usize = 0;
+        for pages in &statements {
+            for page_str in pages {
+                legacy_found += extract_per_page_with_recompiled_regexes(page_str);
+            }
+        }
+        let legacy_elapsed: Duration = started.elapsed();
+
+        // The scanner reads the daily ending balances in the same pass, which the per-page
+        // extractors never did, so it is timed with them included.
+        let started = Instant::now();
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let mut scanned_found: usize = 0;
+        for pages in &statements {
+            let stitched = stitch_pages(Path::new("sample.pdf"), pages, &scanner.page_markers);
+            let (transactions, daily_balances) =
+                scanner.extract_entries(&stitched, &january_2024(), &mut vec![]);
+            scanned_found += transactions.signed_amounts().len() + daily_balances.len();
+        }
+        let scanner_elapsed: Duration = started.elapsed();
+
+        println!(
+            "per-page recompiling: {:?} ({} matches), scanner: {:?} ({} entries)",
+            legacy_elapsed, legacy_found, scanner_elapsed, scanned_found
+        );
+        assert!(scanner_elapsed < legacy_elapsed);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::parse_utils::TransactionKind;
+use crate::profile::{labels_pattern, LocaleSettings};
+
+/// Regex matching a whole line that holds a section heading in `locale`, captured as
+/// `heading`, which may be marked as continued from the previous page.
+pub(crate) fn heading_pattern(locale: &LocaleSettings) -> String {
+    let labels = &locale.labels;
+    let headings: Vec<&str> = labels
+        .headings
+        .iter()
+        .map(|(_, heading)| heading.as_str())
+        .collect();
+    format!(
+        r"^[ \t]*(?<heading>{})(?:[ \t]*\({}\))?[ \t]*$",
+        labels_pattern(&headings),
+        regex::escape(&labels.continued)
+    )
+}
+
+/// The headings a statement groups its transactions under.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum StatementSection {
+    Summary,
+    DepositsAndAdditions,
+    AtmAndDebitCardWithdrawals,
+    ElectronicWithdrawals,
+    Fees,
+    DailyEndingBalance,
+}
+
+impl StatementSection {
+    pub const ALL: [StatementSection; 6] = [
+        StatementSection::Summary,
+        StatementSection::DepositsAndAdditions,
+        StatementSection::AtmAndDebitCardWithdrawals,
+        StatementSection::ElectronicWithdrawals,
+        StatementSection::Fees,
+        StatementSection::DailyEndingBalance,
+    ];
+
+    /// The heading as printed on US statements, which also names the section in reports.
+    pub fn heading(&self) -> &'static str {
+        match self {
+            StatementSection::Summary => "CHECKING SUMMARY",
+            StatementSection::DepositsAndAdditions => "DEPOSITS AND ADDITIONS",
+            StatementSection::AtmAndDebitCardWithdrawals => "ATM & DEBIT CARD WITHDRAWALS",
+            StatementSection::ElectronicWithdrawals => "ELECTRONIC WITHDRAWALS",
+            StatementSection::Fees => "FEES",
+            StatementSection::DailyEndingBalance => "DAILY ENDING BALANCE",
+        }
+    }
+
+    /// The label of the section's total in the summary box, for sections that list
+    /// transactions.
+    pub fn summary_label(&self) -> Option<&'static str> {
+        match self {
+            StatementSection::DepositsAndAdditions => Some("Deposits and Additions"),
+            StatementSection::AtmAndDebitCardWithdrawals => Some("ATM & Debit Card Withdrawals"),
+            StatementSection::ElectronicWithdrawals => Some("Electronic Withdrawals"),
+            StatementSection::Fees => Some("Fees"),
+            StatementSection::Summary | StatementSection::DailyEndingBalance => None,
+        }
+    }
+
+    /// Whether the transactions listed under the heading add to or draw from the account.
+    /// Sections that do not list transactions have no direction.
+    pub fn direction(&self) -> Option<TransactionKind> {
+        match self {
+            StatementSection::DepositsAndAdditions => Some(TransactionKind::Credit),
+            StatementSection::AtmAndDebitCardWithdrawals
+            | StatementSection::ElectronicWithdrawals
+            | StatementSection::Fees => Some(TransactionKind::Debit),
+            StatementSection::Summary | StatementSection::DailyEndingBalance => None,
+        }
+    }
+}
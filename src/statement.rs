@@ -0,0 +1,340 @@
+use crate::balance_utils::DailyBalance;
+use crate::category_utils::Categorizer;
+use crate::errors::{BalanceKind, ParseError};
+use crate::io_utils::write_statement_text;
+use crate::ledger_utils::{Ledger, LedgerError};
+use crate::money_utils::{Currency, Money};
+use crate::page_utils::{check_page_completeness, stitch_pages, PageIssue};
+use crate::parse_utils::{parse_begin_or_end_bal_amt, ExtractedTransactions, PageRef};
+use crate::period_utils::{parse_statement_period, StatementPeriod};
+use crate::pyo3_pdf_service::{extract_text_from_page, get_page_count};
+use crate::scanner::StatementScanner;
+use crate::subprocess_pdf_service::{extract_pages_in_subprocess, PythonCommand};
+use crate::summary_utils::{parse_summary_total, SummaryTotal};
+use chrono::NaiveDate;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Everything extracted from one statement.
+///
+/// Problems that only affect part of the statement, such as a page without text or a
+/// transaction line with an unreadable amount, are collected in `warnings` instead of
+/// aborting the run.
+#[derive(Debug)]
+pub(crate) struct StatementResult {
+    pub file: PathBuf,
+    pub period: StatementPeriod,
+    /// The account number printed in the header, which tells the statements of different
+    /// accounts apart.
+    pub account_number: Option<String>,
+    pub currency: Currency,
+    pub begin_bal: Option<Money>,
+    pub ending_bal: Option<Money>,
+    pub transactions: ExtractedTransactions,
+    /// The rows of the daily ending balance table.
+    pub daily_balances: Vec<DailyBalance>,
+    /// Pages that are unreadable, missing, duplicated or out of order. Any issue means
+    /// the statement is incomplete.
+    pub page_issues: Vec<PageIssue>,
+    /// The category totals printed in the summary box.
+    pub summary_totals: Vec<SummaryTotal>,
+    pub warnings: Vec<ParseError>,
+}
+
+/// Where the text of a statement's pages is extracted.
+#[derive(Debug, Clone)]
+pub(crate) enum PageExtractor {
+    /// Through the Python interpreter embedded in this process.
+    InProcess,
+    /// Through a separate Python process per statement, started with the given interpreter.
+    Subprocess { python: PythonCommand },
+}
+
+/// Extracts the raw text of every page.
+///
+/// A page pypdf cannot read is reported in `warnings` and left empty, so the pages after it
+/// keep their page numbers.
+pub(crate) fn read_statement_pages(
+    pdf_file_path: &PathBuf,
+    extractor: &PageExtractor,
+    warnings: &mut Vec<ParseError>,
+) -> Result<Vec<String>, ParseError> {
+    let page_texts: Vec<Result<String, String>> = match extractor {
+        PageExtractor::InProcess => read_pages_in_process(pdf_file_path)?,
+        PageExtractor::Subprocess { python } => extract_pages_in_subprocess(python, pdf_file_path)
+            .map_err(|e| ParseError::Extractor {
+                file: pdf_file_path.clone(),
+                message: e.to_string(),
+            })?,
+    };
+    let mut pdf_page_strs: Vec<String> = Vec::new();
+    for (page_num, page_text) in page_texts.into_iter().enumerate() {
+        match page_text {
+            Ok(pdf_page_str) => pdf_page_strs.push(pdf_page_str),
+            Err(message) => {
+                warnings.push(ParseError::PageExtraction {
+                    file: pdf_file_path.clone(),
+                    page: page_num + 1,
+                    message,
+                });
+                pdf_page_strs.push(String::new());
+            }
+        }
+    }
+    Ok(pdf_page_strs)
+}
+
+fn read_pages_in_process(
+    pdf_file_path: &PathBuf,
+) -> Result<Vec<Result<String, String>>, ParseError> {
+    let pg_count: u8 = get_page_count(pdf_file_path).map_err(|e| ParseError::PageCount {
+        file: pdf_file_path.clone(),
+        message: e.to_string(),
+    })?;
+    let page_range: Range<u8> = 0..pg_count;
+    Ok(page_range
+        .map(|pypdf_reader_page_index| {
+            extract_text_from_page(pdf_file_path, pypdf_reader_page_index)
+                .map_err(|e| e.to_string())
+        })
+        .collect())
+}
+
+/// Parses the statement period, balances and transactions from the page texts.
+///
+/// Fails only when the statement period cannot be read, since every transaction date
+/// depends on it, or when the period ends after `as_of`, which means it was misread.
+/// Passing the as-of date in, rather than reading the clock, keeps results reproducible.
+pub(crate) fn parse_statement_pages(
+    pdf_file_path: &Path,
+    pdf_page_strs: &[String],
+    scanner: &StatementScanner,
+    as_of: NaiveDate,
+    mut warnings: Vec<ParseError>,
+) -> Result<StatementResult, ParseError> {
+    let profile = &scanner.profile;
+    let mut period: Option<StatementPeriod> = None;
+    let mut period_error: Option<ParseError> = None;
+    let mut account_number: Option<String> = None;
+    let mut begin_bal: Option<Money> = None;
+    let mut ending_bal: Option<Money> = None;
+    let mut summary_totals: Vec<SummaryTotal> = vec![];
+
+    for (page_num, page_str) in pdf_page_strs.iter().enumerate() {
+        let page_ref = PageRef {
+            file: pdf_file_path,
+            page: page_num + 1,
+        };
+        if period.is_none() {
+            if let Some(period_capture) = scanner.period_re.captures(page_str) {
+                match parse_statement_period(period_capture, &profile.locale, &page_ref) {
+                    Ok(parsed_period) => period = Some(parsed_period),
+                    Err(e) => {
+                        period_error.get_or_insert(e);
+                    }
+                }
+            };
+        }
+        if account_number.is_none() {
+            account_number = scanner
+                .account_number_re
+                .captures(page_str)
+                .map(|captures| String::from(&captures["number"]));
+        }
+        if begin_bal.is_none() {
+            if let Some(bal_capture) = scanner.begin_balance_re.captures_iter(page_str).next() {
+                match parse_begin_or_end_bal_amt(bal_capture, profile, &page_ref) {
+                    Ok(bal) => begin_bal = Some(bal),
+                    Err(e) => warnings.push(e),
+                }
+            }
+        }
+        if ending_bal.is_none() {
+            if let Some(bal_capture) = scanner.end_balance_re.captures_iter(page_str).next() {
+                match parse_begin_or_end_bal_amt(bal_capture, profile, &page_ref) {
+                    Ok(bal) => ending_bal = Some(bal),
+                    Err(e) => warnings.push(e),
+                }
+            }
+        }
+        for summary_capture in scanner.summary_total_re.captures_iter(page_str) {
+            match parse_summary_total(summary_capture, profile, &page_ref) {
+                Ok(summary_total)
+                    if !summary_totals
+                        .iter()
+                        .any(|found| found.section == summary_total.section) =>
+                {
+                    summary_totals.push(summary_total)
+                }
+                Ok(_) => {}
+                Err(e) => warnings.push(e),
+            }
+        }
+    }
+
+    // Every transaction date is read within the period, so without one nothing is extracted.
+    let period = period.ok_or_else(|| {
+        period_error.unwrap_or_else(|| ParseError::MissingStatementPeriod {
+            file: pdf_file_path.to_path_buf(),
+        })
+    })?;
+    if period.end > as_of {
+        return Err(ParseError::PeriodAfterAsOf {
+            file: pdf_file_path.to_path_buf(),
+            period,
+            as_of,
+        });
+    }
+    let unreadable_pages: Vec<usize> = warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            ParseError::PageExtraction { page, .. } => Some(*page),
+            _ => None,
+        })
+        .collect();
+    let page_issues =
+        check_page_completeness(pdf_page_strs, &unreadable_pages, &scanner.page_markers);
+    let stitched = stitch_pages(pdf_file_path, pdf_page_strs, &scanner.page_markers);
+    let (transactions, daily_balances) = scanner.extract_entries(&stitched, &period, &mut warnings);
+    if begin_bal.is_none() {
+        warnings.push(ParseError::MissingBalance {
+            file: pdf_file_path.to_path_buf(),
+            kind: BalanceKind::Beginning,
+        });
+    }
+    if ending_bal.is_none() {
+        warnings.push(ParseError::MissingBalance {
+            file: pdf_file_path.to_path_buf(),
+            kind: BalanceKind::Ending,
+        });
+    }
+
+    Ok(StatementResult {
+        file: pdf_file_path.to_path_buf(),
+        period,
+        account_number,
+        currency: profile.currency,
+        begin_bal,
+        ending_bal,
+        transactions,
+        daily_balances,
+        page_issues,
+        summary_totals,
+        warnings,
+    })
+}
+
+impl StatementResult {
+    pub fn is_complete(&self) -> bool {
+        self.page_issues.is_empty()
+    }
+
+    /// The statement booked into a ledger of its own.
+    pub fn ledger(&self, categorizer: &Categorizer) -> Result<Ledger, LedgerError> {
+        let mut ledger = Ledger::new(categorizer.chart_of_accounts(), self.currency);
+        self.post_to(&mut ledger, categorizer, true)?;
+        Ok(ledger)
+    }
+
+    /// Books every extracted transaction, categorized by `categorizer`, into `ledger`,
+    /// after the beginning balance if `with_opening_balance` and it was read.
+    pub fn post_to(
+        &self,
+        ledger: &mut Ledger,
+        categorizer: &Categorizer,
+        with_opening_balance: bool,
+    ) -> Result<(), LedgerError> {
+        if let (true, Some(begin_bal)) = (with_opening_balance, self.begin_bal) {
+            ledger.post_opening_balance(self.period.start, begin_bal)?;
+        }
+        ledger.post_transactions(&self.transactions, categorizer)
+    }
+}
+
+/// Reads the pages of one statement, saves their text in `txt_dir` and parses them.
+pub(crate) fn process_statement(
+    pdf_file_path: &PathBuf,
+    extractor: &PageExtractor,
+    scanner: &StatementScanner,
+    as_of: NaiveDate,
+    txt_dir: &Path,
+) -> Result<StatementResult, ParseError> {
+    let mut warnings: Vec<ParseError> = vec![];
+    let pdf_page_strs = read_statement_pages(pdf_file_path, extractor, &mut warnings)?;
+    if let Err(e) = write_statement_text(txt_dir, pdf_file_path, &pdf_page_strs) {
+        warnings.push(ParseError::TextNotWritten {
+            file: pdf_file_path.clone(),
+            message: e.to_string(),
+        });
+    }
+    parse_statement_pages(pdf_file_path, &pdf_page_strs, scanner, as_of, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{LocalePreset, ParsingProfile};
+    use crate::summary_utils::summary_mismatches;
+    use fastnum::decimal::Context;
+    use fastnum::D256;
+
+    #[test]
+    fn reports_an_extractor_that_cannot_run() {
+        let extractor = PageExtractor::Subprocess {
+            python: PythonCommand::new("/nonexistent/python3"),
+        };
+        let mut warnings = vec![];
+        let result =
+            read_statement_pages(&PathBuf::from("statement.pdf"), &extractor, &mut warnings);
+        assert!(
+            matches!(&result, Err(ParseError::Extractor { message, .. }) if message.contains("/nonexistent/python3")),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn parses_a_european_statement() {
+        let pages = [
+            "Musterfirma GmbH\nKontonummer: 1234-5678\n1. Januar 2024 bis 31. Januar 2024\nKONTOÜBERSICHT\nAnfangssaldo 1.000,00 €\nGutschriften 2 1.500,00 €\nÜberweisungen und Lastschriften 1 -200,00 €\nEntgelte 1 -15,00 €\nEndsaldo 2.285,00 €\nGUTSCHRIFTEN\nDATUM BESCHREIBUNG BETRAG\n05.01. Kunde A Zahlung 1.000,00 €\nSeite 1 von 2\n",
+            "Musterfirma GmbH\nKontonummer: 1234-5678\nGUTSCHRIFTEN (Fortsetzung)\nDATUM BESCHREIBUNG BETRAG\n20.01. Kunde B Zahlung 500,00 €\nSumme Gutschriften 1.500,00 €\nÜBERWEISUNGEN UND LASTSCHRIFTEN\n10.01. Vermieter Bill Xfer Ref 1 200,00 €\nSumme Überweisungen und Lastschriften 200,00 €\nENTGELTE\n31.01. Kontoführung 15,00 €\nSumme Entgelte 15,00 €\nTAGESENDSALDO\n05.01. 2.000,00 € 10.01. 1.800,00 €\n20.01. 2.300,00 € 31.01. 2.285,00 €\nSeite 2 von 2\n",
+        ]
+        .map(String::from);
+        let scanner = StatementScanner::new(ParsingProfile::for_locale(LocalePreset::Eu));
+        let as_of = NaiveDate::from_ymd_opt(2024, 2, 15).unwrap();
+        let statement =
+            parse_statement_pages(Path::new("auszug.pdf"), &pages, &scanner, as_of, vec![])
+                .unwrap();
+        let eur = |s: &str| {
+            Money::new(
+                D256::from_str(s, Context::default()).unwrap(),
+                Currency::Eur,
+            )
+        };
+
+        assert!(statement.warnings.is_empty(), "{:?}", statement.warnings);
+        assert!(statement.is_complete(), "{:?}", statement.page_issues);
+        assert_eq!(
+            (statement.period.start, statement.period.end),
+            (
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()
+            )
+        );
+        assert_eq!(statement.account_number.as_deref(), Some("1234-5678"));
+        assert_eq!(statement.begin_bal, Some(eur("1000.00")));
+        assert_eq!(statement.ending_bal, Some(eur("2285.00")));
+
+        let transactions = &statement.transactions;
+        assert_eq!(transactions.deposits.len(), 2);
+        assert_eq!(transactions.deposits[1].source.page(), 2);
+        assert_eq!(transactions.payments.len(), 1);
+        assert_eq!(transactions.fees.len(), 1);
+        assert_eq!(transactions.signed_amounts().len(), 4);
+        assert_eq!(statement.daily_balances.len(), 4);
+        assert_eq!(statement.summary_totals.len(), 3);
+        assert!(summary_mismatches(&statement.summary_totals, transactions)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Separates the page records written by `pdf_parser.write_pages_of_pdf`.
+const PAGE_SEPARATOR: char = '\0';
+
+/// The Python interpreter that extracts the pages, and the module search path it is
+/// started with.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PythonCommand {
+    pub program: PathBuf,
+    /// Passed to the process as `PYTHONPATH`, if given.
+    pub search_path: Option<OsString>,
+}
+
+impl PythonCommand {
+    /// The interpreter found as `program`, with the module search path of the environment.
+    pub fn new(program: &str) -> Self {
+        Self {
+            program: PathBuf::from(program),
+            search_path: None,
+        }
+    }
+}
+
+/// Extracts the text of every page in a separate Python process, so several statements
+/// can be read at once without contending for the interpreter lock of this process.
+///
+/// Each page is either its text or the message pypdf gave when the page could not be read.
+pub(crate) fn extract_pages_in_subprocess(
+    python: &PythonCommand,
+    pdf_file_path: &Path,
+) -> Result<Vec<Result<String, String>>, Box<dyn std::error::Error>> {
+    let mut command = Command::new(&python.program);
+    command
+        .args(["-m", "statements_to_books.pdf_parser"])
+        .arg(pdf_file_path);
+    if let Some(search_path) = &python.search_path {
+        command.env("PYTHONPATH", search_path);
+    }
+    let output = command
+        .output()
+        .map_err(|e| format!("{} not run: {}", python.program.display(), e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().into());
+    }
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(parse_page_records(&stdout))
+}
+
+fn parse_page_records(stdout: &str) -> Vec<Result<String, String>> {
+    if stdout.is_empty() {
+        return vec![];
+    }
+    stdout
+        .split(PAGE_SEPARATOR)
+        .map(|record| match record.split_once('\n') {
+            Some(("ok", page_text)) => Ok(String::from(page_text)),
+            Some(("err", message)) => Err(String::from(message)),
+            _ => Err(format!("unreadable page record {:?}", record)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_page_records() {
+        assert_eq!(parse_page_records(""), vec![]);
+        assert_eq!(
+            parse_page_records("ok\nfirst\npage\n\0err\nbad xref\0ok\n"),
+            vec![
+                Ok(String::from("first\npage\n")),
+                Err(String::from("bad xref")),
+                Ok(String::new()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use crate::ledger_utils::AccountType;
+use crate::parse_utils::{TransactionKind, TransactionSubtype};
+use fastnum::decimal::{Context, Decimal};
+use fastnum::D256;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LabelsError {
+    Io { path: String, message: String },
+    InvalidLine { line_number: usize, text: String },
+}
+
+impl fmt::Display for LabelsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelsError::Io { path, message } => {
+                write!(f, "category labels not read from {}. {}", path, message)
+            }
+            LabelsError::InvalidLine { line_number, text } => {
+                write!(
+                    f,
+                    "category labels line {} not parsed: {:?}",
+                    line_number, text
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LabelsError {}
+
+/// A transaction categorized by hand.
+///
+/// In a labels file each is one `category,kind,amount,description` line, for example
+/// `Software,DebitCardPurchase,12.00,01/05 Card Purchase 01/04 Github Card 4821 12.00`.
+/// The description runs to the end of the line and may contain commas.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LabeledTransaction {
+    pub category: String,
+    pub kind: TransactionSubtype,
+    /// The amount as listed on the statement.
+    pub amount: Decimal<4>,
+    pub description: String,
+}
+
+impl fmt::Display for LabeledTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{:?},{:.2},{}",
+            self.category, self.kind, self.amount, self.description
+        )
+    }
+}
+
+pub(crate) fn load_labels(path: &Path) -> Result<Vec<LabeledTransaction>, LabelsError> {
+    let labels_str = std::fs::read_to_string(path).map_err(|e| LabelsError::Io {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    })?;
+    parse_labels(&labels_str)
+}
+
+/// Reads a labels file. Blank lines and lines starting with `#` are ignored.
+pub(crate) fn parse_labels(labels_str: &str) -> Result<Vec<LabeledTransaction>, LabelsError> {
+    let mut labels = vec![];
+    for (line_index, line) in labels_str.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid_line = || LabelsError::InvalidLine {
+            line_number: line_index + 1,
+            text: String::from(line),
+        };
+        let fields: Vec<&str> = line.splitn(4, ',').map(str::trim).collect();
+        let [category, kind, amount, description] = fields[..] else {
+            return Err(invalid_line());
+        };
+        if category.is_empty() {
+            return Err(invalid_line());
+        }
+        labels.push(LabeledTransaction {
+            category: String::from(category),
+            kind: kind.parse().map_err(|_| invalid_line())?,
+            amount: D256::from_str(amount, Context::default()).map_err(|_| invalid_line())?,
+            description: String::from(description),
+        });
+    }
+    Ok(labels)
+}
+
+/// The words of the description, the transaction kind and the order of magnitude of the
+/// amount. Numbers in the description, such as dates and reference numbers, are left out
+/// because they rarely repeat.
+fn features(kind: TransactionSubtype, description: &str, amount: Decimal<4>) -> Vec<String> {
+    let mut features: Vec<String> = description
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| word.chars().count() > 1)
+        .map(str::to_lowercase)
+        .collect();
+    features.push(format!("kind:{:?}", kind));
+    let whole_digits = format!("{:.0}", amount.abs()).len();
+    features.push(format!("digits:{}", whole_digits));
+    features
+}
+
+/// A category suggested for a transaction, with the model's probability that it is right.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Suggestion<'a> {
+    pub category: &'a str,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone)]
+struct CategoryCounts {
+    category: String,
+    examples: usize,
+    credits: usize,
+    feature_counts: HashMap<String, usize>,
+    feature_total: usize,
+}
+
+/// A multinomial naive Bayes classifier over the [`features`] of labeled transactions,
+/// with add-one smoothing. It is trained and run locally.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NaiveBayes {
+    categories: Vec<CategoryCounts>,
+    vocabulary: HashSet<String>,
+    examples: usize,
+}
+
+impl NaiveBayes {
+    pub fn train(labels: &[LabeledTransaction]) -> Self {
+        let mut model = NaiveBayes::default();
+        for label in labels {
+            let index = match model
+                .categories
+                .iter()
+                .position(|counts| counts.category == label.category)
+            {
+                Some(index) => index,
+                None => {
+                    model.categories.push(CategoryCounts {
+                        category: label.category.clone(),
+                        examples: 0,
+                        credits: 0,
+                        feature_counts: HashMap::new(),
+                        feature_total: 0,
+                    });
+                    model.categories.len() - 1
+                }
+            };
+            let counts = &mut model.categories[index];
+            counts.examples += 1;
+            if label.kind.debit_or_credit() == TransactionKind::Credit {
+                counts.credits += 1;
+            }
+            for feature in features(label.kind, &label.description, label.amount) {
+                *counts.feature_counts.entry(feature.clone()).or_insert(0) += 1;
+                counts.feature_total += 1;
+                model.vocabulary.insert(feature);
+            }
+            model.examples += 1;
+        }
+        model
+    }
+
+    /// Every category the model was trained on, with the account type it is booked as:
+    /// revenue if all its examples are deposits, expense otherwise.
+    pub fn categories(&self) -> impl Iterator<Item = (&str, AccountType)> {
+        self.categories.iter().map(|counts| {
+            let account_type = if counts.credits == counts.examples {
+                AccountType::Revenue
+            } else {
+                AccountType::Expense
+            };
+            (counts.category.as_str(), account_type)
+        })
+    }
+
+    /// The most likely category of a transaction among those with examples on its side,
+    /// debit or credit, so a deposit is never suggested an expense category only ever
+    /// labeled on purchases. `None` if no category has examples on that side.
+    pub fn suggest(
+        &self,
+        kind: TransactionSubtype,
+        description: &str,
+        amount: Decimal<4>,
+    ) -> Option<Suggestion<'_>> {
+        let features = features(kind, description, amount);
+        let vocabulary_size = self.vocabulary.len() as f64;
+        let candidates: Vec<&CategoryCounts> = self
+            .categories
+            .iter()
+            .filter(|counts| match kind.debit_or_credit() {
+                TransactionKind::Credit => counts.credits > 0,
+                TransactionKind::Debit => counts.credits < counts.examples,
+            })
+            .collect();
+        let log_posteriors: Vec<f64> = candidates
+            .iter()
+            .map(|counts| {
+                let log_prior = (counts.examples as f64 / self.examples as f64).ln();
+                let denominator = counts.feature_total as f64 + vocabulary_size;
+                log_prior
+                    + features
+                        .iter()
+                        .filter(|feature| self.vocabulary.contains(*feature))
+                        .map(|feature| {
+                            let count = counts.feature_counts.get(feature).copied().unwrap_or(0);
+                            ((count as f64 + 1.0) / denominator).ln()
+                        })
+                        .sum::<f64>()
+            })
+            .collect();
+        let (best, best_log_posterior) = log_posteriors
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        let evidence: f64 = log_posteriors
+            .iter()
+            .map(|log_posterior| (log_posterior - best_log_posterior).exp())
+            .sum();
+        Some(Suggestion {
+            category: &candidates[best].category,
+            confidence: 1.0 / evidence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_the_category_of_similar_labeled_transactions() {
+        let labels = parse_labels(
+            "# category,kind,amount,description\n\
+             Software,DebitCardPurchase,12.00,11/05 Card Purchase 11/04 Github Card 4821 12.00\n\
+             Software,DebitCardPurchase,49.99,12/05 Card Purchase 12/04 Github Card 4821 49.99\n\
+             Software,DebitCardPurchase,54.99,12/09 Card Purchase 12/08 Adobe Card 4821 54.99\n\
+             Fuel,DebitCardPurchase,61.20,11/21 Card Purchase 11/21 Shell Oil Card 4821 61.20\n\
+             Fuel,DebitCardPurchase,48.10,12/21 Card Purchase 12/21 Shell Oil Card 4821 48.10\n\
+             Rent,OnlinePayment,1800.00,12/01 Landlord Props Bill Xfer Ref 20607, Suite 5 $1,800.00\n\
+             Client Income,Deposit,2565.07,11/26 Orig CO Name:Globex Corp Orig ID:7164579\n",
+        )
+        .unwrap();
+        assert_eq!(
+            labels[5].description,
+            "12/01 Landlord Props Bill Xfer Ref 20607, Suite 5 $1,800.00"
+        );
+        let model = NaiveBayes::train(&labels);
+
+        let dec = |s: &str| D256::from_str(s, Context::default()).unwrap();
+        let github = model
+            .suggest(
+                TransactionSubtype::DebitCardPurchase,
+                "01/05 Card Purchase 01/04 Github Card 4821 19.00",
+                dec("19.00"),
+            )
+            .unwrap();
+        assert_eq!(github.category, "Software");
+        assert!(github.confidence > 0.9, "{:?}", github);
+        let shell = model
+            .suggest(
+                TransactionSubtype::DebitCardPurchase,
+                "01/21 Card Purchase 01/21 Shell Oil Card 4821 55.00",
+                dec("55.00"),
+            )
+            .unwrap();
+        assert_eq!(shell.category, "Fuel");
+        let unfamiliar = model
+            .suggest(
+                TransactionSubtype::DebitCardPurchase,
+                "01/09 Card Purchase 01/09 Staples Card 4821 20.95",
+                dec("20.95"),
+            )
+            .unwrap();
+        assert!(unfamiliar.confidence < github.confidence);
+
+        let categories: Vec<(&str, AccountType)> = model.categories().collect();
+        assert!(categories.contains(&("Client Income", AccountType::Revenue)));
+        assert!(categories.contains(&("Rent", AccountType::Expense)));
+        assert!(NaiveBayes::default()
+            .suggest(TransactionSubtype::Fee, "Monthly Service Fee", dec("10"))
+            .is_none());
+        assert!(parse_labels("Software,Purchase,12.00,Github\n").is_err());
+        assert!(parse_labels("Software,DebitCardPurchase,12.00\n").is_err());
+    }
+
+    #[test]
+    fn suggests_only_categories_labeled_on_the_same_side() {
+        let labels = parse_labels(
+            "Office Supplies,DebitCardPurchase,42.00,01/05 Card Purchase 01/04 AMAZON Card 4821\n\
+             Office Supplies,DebitCardPurchase,18.50,02/05 Card Purchase 02/04 AMAZON Card 4821\n\
+             Fuel,DebitCardPurchase,61.20,11/21 Card Purchase 11/21 Shell Oil Card 4821 61.20\n",
+        )
+        .unwrap();
+        let model = NaiveBayes::train(&labels);
+        let dec = |s: &str| D256::from_str(s, Context::default()).unwrap();
+        assert_eq!(
+            model.suggest(
+                TransactionSubtype::Deposit,
+                "03/09 AMAZON Card Purchase Refund",
+                dec("42.00")
+            ),
+            None
+        );
+
+        let mut with_income = labels.clone();
+        with_income.extend(
+            parse_labels("Client Income,Deposit,2565.07,11/26 Orig CO Name:Globex Corp\n").unwrap(),
+        );
+        let model = NaiveBayes::train(&with_income);
+        let refund = model
+            .suggest(
+                TransactionSubtype::Deposit,
+                "03/09 AMAZON Card Purchase Refund",
+                dec("42.00"),
+            )
+            .unwrap();
+        assert_eq!(refund.category, "Client Income");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::errors::{ParseError, SourceLocation};
+use crate::money_utils::{parse_money_token, CurrencyMismatch, Money};
+use crate::parse_utils::{ExtractedTransactions, PageRef};
+use crate::profile::{labels_pattern, LocaleSettings, ParsingProfile};
+use crate::section_utils::StatementSection;
+use regex::Captures;
+use std::fmt;
+
+/// Regex matching a category total in the summary box, such as
+/// `Deposits and Additions 3 $1,200.00`, with the label and amount captured.
+pub(crate) fn summary_total_pattern(locale: &LocaleSettings, amount: &str) -> String {
+    let labels: Vec<&str> = locale
+        .labels
+        .summary_labels
+        .iter()
+        .map(|(_, label)| label.as_str())
+        .collect();
+    format!(
+        r"(?m)^[ \t]*(?<label>{})(?:[ \t]+\d+)?[ \t]+(?<amount>{amount})[ \t]*$",
+        labels_pattern(&labels)
+    )
+}
+
+/// A category total printed in the statement's summary box.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SummaryTotal {
+    pub section: StatementSection,
+    /// The total as printed, without the minus sign some banks put before withdrawals.
+    pub amount: Money,
+    pub location: SourceLocation,
+}
+
+pub(crate) fn parse_summary_total(
+    summary_captures: Captures,
+    profile: &ParsingProfile,
+    page_ref: &PageRef,
+) -> Result<SummaryTotal, ParseError> {
+    let amount_match = summary_captures
+        .name("amount")
+        .expect("group is not optional");
+    let location = page_ref.location(amount_match.start());
+    let section = profile
+        .locale
+        .labels
+        .section_for_summary_label(&summary_captures["label"])
+        .expect("the pattern only matches summary labels");
+    let token = parse_money_token(amount_match.as_str(), &profile.locale).map_err(|_| {
+        ParseError::InvalidAmount {
+            location: location.clone(),
+            text: String::from(amount_match.as_str()),
+        }
+    })?;
+    Ok(SummaryTotal {
+        section,
+        amount: Money::new(token.amount.abs(), profile.currency),
+        location,
+    })
+}
+
+/// A summary box total that differs from the transactions extracted under its section.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SummaryMismatch {
+    pub section: StatementSection,
+    pub stated: Money,
+    pub extracted: Money,
+    pub location: SourceLocation,
+}
+
+impl fmt::Display for SummaryMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let difference = Money::new(
+            self.stated.amount - self.extracted.amount,
+            self.stated.currency,
+        );
+        write!(
+            f,
+            "{}: summary shows {} {:.2}, extracted transactions add up to {:.2}, difference {:.2}",
+            self.location,
+            self.section
+                .summary_label()
+                .unwrap_or(self.section.heading()),
+            self.stated,
+            self.extracted,
+            difference
+        )
+    }
+}
+
+/// Compares every summary box total with the sum of the transactions listed under the
+/// same section, whatever kind they were classified as.
+pub(crate) fn summary_mismatches(
+    summary_totals: &[SummaryTotal],
+    transactions: &ExtractedTransactions,
+) -> Result<Vec<SummaryMismatch>, CurrencyMismatch> {
+    let mut mismatches: Vec<SummaryMismatch> = vec![];
+    for summary_total in summary_totals {
+        let extracted =
+            transactions.section_total(summary_total.section, summary_total.amount.currency)?;
+        if extracted != summary_total.amount {
+            mismatches.push(SummaryMismatch {
+                section: summary_total.section,
+                stated: summary_total.amount,
+                extracted,
+                location: summary_total.location.clone(),
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::StatementScanner;
+    use crate::statement::parse_statement_pages;
+    use chrono::NaiveDate;
+    use fastnum::decimal::Context;
+    use fastnum::D256;
+    use std::path::Path;
+
+    #[test]
+    fn reports_categories_that_differ_from_extracted_transactions() {
+        let page = String::from(
+            "January 1, 2024 through January 31, 2024\nCHECKING SUMMARY\nINSTANCES AMOUNT\nBeginning Balance $1,000.00\nDeposits and Additions 2 $1,500.00\nElectronic Withdrawals 2 -$300.00\nFees 1 -$15.00\nEnding Balance $2,185.00\nDEPOSITS AND ADDITIONS\n01/05 Client A Deposit $1,000.00\n01/20 Client B Deposit $400.00\nTotal Deposits and Additions $1,400.00\nELECTRONIC WITHDRAWALS\n01/10 Vendor Bill Xfer Ref 1 $100.00\n01/11 Online Transfer To Chk ...9876 $200.00\nTotal Electronic Withdrawals $300.00\nFEES\n01/31 Monthly Service Fee $15.00\nTotal Fees $15.00\n",
+        );
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let as_of = NaiveDate::from_ymd_opt(2024, 2, 15).unwrap();
+        let statement =
+            parse_statement_pages(Path::new("sample.pdf"), &[page], &scanner, as_of, vec![])
+                .unwrap();
+
+        assert_eq!(statement.summary_totals.len(), 3);
+        let mismatches =
+            summary_mismatches(&statement.summary_totals, &statement.transactions).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].section,
+            StatementSection::DepositsAndAdditions
+        );
+        assert_eq!(
+            mismatches[0].extracted.amount,
+            D256::from_str("1400.00", Context::default()).unwrap()
+        );
+        assert_eq!(mismatches[0].location.page, 1);
+    }
+}
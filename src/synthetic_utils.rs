@@ -0,0 +1,747 @@
+use crate::io_utils::write_statement_text;
+use crate::parse_utils::{TransactionKind, TransactionSubtype};
+use crate::pdf_utils::{write_text_pdf, MAX_LINES_PER_PAGE};
+use crate::period_utils::StatementPeriod;
+use crate::rng_utils::XorShift64;
+use crate::section_utils::StatementSection;
+use chrono::{Datelike, Months, NaiveDate};
+use std::fmt;
+use std::path::Path;
+
+const CLIENTS: &[&str] = &["Globex Corp", "Initech LLC", "Umbrella Co", "Hooli Inc"];
+const MERCHANTS: &[&str] = &["Office Depot", "Adobe", "Github", "Shell Oil", "Staples"];
+const VENDORS: &[&str] = &["City Power", "Comcast", "Waste Mgmt", "Landlord Props"];
+const FEE_DESCRIPTIONS: &[&str] = &["Monthly Service Fee", "Wire Fee", "Overdraft Fee"];
+
+/// Lines at the top and bottom of every generated page outside the body: the company, the
+/// statement period and the `Page X of Y` footer.
+const PAGE_FRAME_LINES: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SpecError {
+    Io { path: String, message: String },
+    InvalidLine { line_number: usize, text: String },
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::Io { path, message } => {
+                write!(f, "generator spec not read from {}. {}", path, message)
+            }
+            SpecError::InvalidLine { line_number, text } => {
+                write!(
+                    f,
+                    "generator spec line {} not parsed: {:?}",
+                    line_number, text
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// What to generate: a run of consecutive monthly statements for one account.
+///
+/// A spec file has one `key = value` line per setting, for example `seed = 42`. Blank lines
+/// and lines starting with `#` are ignored, and settings that are left out keep their
+/// defaults.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GeneratorSpec {
+    pub seed: u64,
+    pub company: String,
+    pub statements: usize,
+    /// The first day of the first statement. Each statement covers one month from there.
+    pub first_period_start: NaiveDate,
+    pub transactions_per_statement: usize,
+    /// Lines per page, including the company and period header and the page footer.
+    pub lines_per_page: usize,
+    /// The beginning balance of the first statement, in cents.
+    pub beginning_balance_cents: i64,
+}
+
+impl Default for GeneratorSpec {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            company: String::from("SYNTHETIC WIDGETS LLC"),
+            statements: 3,
+            first_period_start: NaiveDate::from_ymd_opt(2023, 11, 15).unwrap(),
+            transactions_per_statement: 40,
+            lines_per_page: 40,
+            beginning_balance_cents: 250_000,
+        }
+    }
+}
+
+impl GeneratorSpec {
+    pub fn load(path: &Path) -> Result<Self, SpecError> {
+        let spec_str = std::fs::read_to_string(path).map_err(|e| SpecError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&spec_str)
+    }
+
+    pub fn parse(spec_str: &str) -> Result<Self, SpecError> {
+        let mut spec = GeneratorSpec::default();
+        for (line_index, line) in spec_str.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = || SpecError::InvalidLine {
+                line_number: line_index + 1,
+                text: String::from(line),
+            };
+            let (key, value) = line.split_once('=').ok_or_else(invalid_line)?;
+            let value = value.trim();
+            match key.trim() {
+                "seed" => spec.seed = value.parse().map_err(|_| invalid_line())?,
+                "company" => spec.company = String::from(value),
+                "statements" => spec.statements = value.parse().map_err(|_| invalid_line())?,
+                "first_period_start" => {
+                    spec.first_period_start =
+                        NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| invalid_line())?
+                }
+                "transactions_per_statement" => {
+                    spec.transactions_per_statement = value.parse().map_err(|_| invalid_line())?
+                }
+                "lines_per_page" => {
+                    spec.lines_per_page = value
+                        .parse()
+                        .ok()
+                        .filter(|lines| (PAGE_FRAME_LINES + 4..=MAX_LINES_PER_PAGE).contains(lines))
+                        .ok_or_else(invalid_line)?
+                }
+                "beginning_balance" => {
+                    spec.beginning_balance_cents = parse_cents(value).ok_or_else(invalid_line)?
+                }
+                _ => return Err(invalid_line()),
+            }
+        }
+        Ok(spec)
+    }
+}
+
+fn parse_cents(value: &str) -> Option<i64> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, "00"));
+    if fraction.len() != 2 {
+        return None;
+    }
+    Some(whole.parse::<i64>().ok()? * 100 + fraction.parse::<i64>().ok()?)
+}
+
+/// `-$1,234.56` style, as the statements print amounts.
+fn format_cents(cents: i64, currency_symbol: bool) -> String {
+    let digits = format!("{}", cents.abs() / 100);
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!(
+        "{}{}{}.{:02}",
+        if cents < 0 { "-" } else { "" },
+        if currency_symbol { "$" } else { "" },
+        grouped,
+        cents.abs() % 100
+    )
+}
+
+/// A transaction the parser should find in a generated statement.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExpectedTransaction {
+    pub date: NaiveDate,
+    pub subtype: TransactionSubtype,
+    pub section: StatementSection,
+    /// The amount as listed, always positive.
+    pub amount_cents: i64,
+    /// The page the transaction starts on.
+    pub page: usize,
+    lines: Vec<String>,
+}
+
+impl ExpectedTransaction {
+    /// The effect on the balance: positive for deposits, negative for everything else.
+    pub fn signed_cents(&self) -> i64 {
+        match self.section {
+            StatementSection::DepositsAndAdditions => self.amount_cents,
+            _ => -self.amount_cents,
+        }
+    }
+}
+
+/// A generated statement: its page texts and everything the parser should read from them.
+#[derive(Debug, Clone)]
+pub(crate) struct SyntheticStatement {
+    pub name: String,
+    pub period: StatementPeriod,
+    pub beginning_balance_cents: i64,
+    pub ending_balance_cents: i64,
+    pub pdf_page_strs: Vec<String>,
+    pub transactions: Vec<ExpectedTransaction>,
+}
+
+impl SyntheticStatement {
+    /// The expected parse, one `key,value...` line per fact, in the order the
+    /// transactions appear on the statement.
+    pub fn expected_csv(&self) -> String {
+        let mut expected = format!(
+            "# expected parse of {}.pdf\nperiod,{},{}\nbeginning_balance,{}\nending_balance,{}\npages,{}\n# transaction,date,kind,amount,page\n",
+            self.name,
+            self.period.start,
+            self.period.end,
+            format_cents(self.beginning_balance_cents, false).replace(',', ""),
+            format_cents(self.ending_balance_cents, false).replace(',', ""),
+            self.pdf_page_strs.len()
+        );
+        for transaction in &self.transactions {
+            expected.push_str(&format!(
+                "transaction,{},{:?},{},{}\n",
+                transaction.date,
+                transaction.subtype,
+                format_cents(transaction.amount_cents, false).replace(',', ""),
+                transaction.page
+            ));
+        }
+        expected
+    }
+
+    /// Writes `<name>.pdf`, `<name>.txt` with the page texts, and `<name>.expected.csv`.
+    pub fn write_files(&self, out_dir: &Path) -> std::io::Result<()> {
+        let pdf_file_path = out_dir.join(format!("{}.pdf", self.name));
+        std::fs::create_dir_all(out_dir)?;
+        std::fs::write(&pdf_file_path, write_text_pdf(&self.pdf_page_strs))?;
+        write_statement_text(out_dir, &pdf_file_path, &self.pdf_page_strs)?;
+        std::fs::write(
+            out_dir.join(format!("{}.expected.csv", self.name)),
+            self.expected_csv(),
+        )
+    }
+}
+
+/// Generates the statements described by `spec`. The same spec always gives the same
+/// statements, and each statement begins with the previous one's ending balance.
+pub(crate) fn generate_statements(spec: &GeneratorSpec) -> Vec<SyntheticStatement> {
+    let mut rng = XorShift64::new(spec.seed);
+    let mut beginning_balance_cents = spec.beginning_balance_cents;
+    let mut statements: Vec<SyntheticStatement> = vec![];
+    for statement_index in 0..spec.statements {
+        let start = spec.first_period_start + Months::new(statement_index as u32);
+        let end = spec.first_period_start + Months::new(statement_index as u32 + 1);
+        let period = StatementPeriod {
+            start,
+            end: end.pred_opt().unwrap_or(end),
+        };
+        let statement = generate_statement(
+            &mut rng,
+            spec,
+            format!("statement_{:03}", statement_index + 1),
+            period,
+            beginning_balance_cents,
+        );
+        beginning_balance_cents = statement.ending_balance_cents;
+        statements.push(statement);
+    }
+    statements
+}
+
+fn pick<'a>(rng: &mut XorShift64, choices: &[&'a str]) -> &'a str {
+    choices[rng.below(choices.len() as u64) as usize]
+}
+
+fn month_day(date: NaiveDate) -> String {
+    format!("{:02}/{:02}", date.month(), date.day())
+}
+
+/// One transaction of a random kind, with the lines it is printed on.
+fn generate_transaction(rng: &mut XorShift64, date: NaiveDate) -> ExpectedTransaction {
+    let md = month_day(date);
+    let (subtype, section, amount_cents, lines) = match rng.below(10) {
+        0..=2 => {
+            let amount_cents = rng.between(50_000, 500_000) as i64;
+            let amount = format_cents(amount_cents, true);
+            let lines = if rng.below(2) == 0 {
+                vec![
+                    format!(
+                        "{} Orig CO Name:{} Orig ID:{} Descr:Payments Sec:CCD",
+                        md,
+                        pick(rng, CLIENTS),
+                        rng.between(1_000_000, 9_999_999)
+                    ),
+                    format!(
+                        "Trace#:{} {}",
+                        rng.between(100_000_000, 999_999_999),
+                        amount
+                    ),
+                ]
+            } else {
+                vec![format!(
+                    "{} Deposit {} {}",
+                    md,
+                    rng.between(10_000, 99_999),
+                    amount
+                )]
+            };
+            (
+                TransactionSubtype::Deposit,
+                StatementSection::DepositsAndAdditions,
+                amount_cents,
+                lines,
+            )
+        }
+        3 => {
+            let amount_cents = rng.between(500, 40_000) as i64;
+            let line = format!(
+                "{} Card Purchase {} {} Card 4821 {}",
+                md,
+                md,
+                pick(rng, MERCHANTS),
+                format_cents(amount_cents, false)
+            );
+            (
+                TransactionSubtype::DebitCardPurchase,
+                StatementSection::AtmAndDebitCardWithdrawals,
+                amount_cents,
+                vec![line],
+            )
+        }
+        4 => {
+            let amount_cents = rng.between(500, 40_000) as i64;
+            let line = format!(
+                "{} Recurring Card Purchase {} {} Card 4821 {}",
+                md,
+                md,
+                pick(rng, MERCHANTS),
+                format_cents(amount_cents, false)
+            );
+            (
+                TransactionSubtype::DebitCardPurchase,
+                StatementSection::ElectronicWithdrawals,
+                amount_cents,
+                vec![line],
+            )
+        }
+        5 | 6 => {
+            let amount_cents = rng.between(2_000, 150_000) as i64;
+            let line = if rng.below(2) == 0 {
+                format!(
+                    "{} {} Bill Xfer Ref {} {}",
+                    md,
+                    pick(rng, VENDORS),
+                    rng.between(10_000, 99_999),
+                    format_cents(amount_cents, true)
+                )
+            } else {
+                format!(
+                    "{} {} ACH Pmt PPD ID: {} {}",
+                    md,
+                    pick(rng, VENDORS),
+                    rng.between(1_000_000, 9_999_999),
+                    format_cents(amount_cents, false)
+                )
+            };
+            (
+                TransactionSubtype::OnlinePayment,
+                StatementSection::ElectronicWithdrawals,
+                amount_cents,
+                vec![line],
+            )
+        }
+        7 | 8 => {
+            let amount_cents = rng.between(10_000, 200_000) as i64;
+            let first_line = format!(
+                "{} Online Transfer To Chk ...{} Transaction#: {}",
+                md,
+                rng.between(1000, 9999),
+                rng.between(10_000_000, 99_999_999)
+            );
+            let amount = format_cents(amount_cents, false);
+            let lines = if rng.below(2) == 0 {
+                vec![format!("{} {}", first_line, amount)]
+            } else {
+                vec![first_line, amount]
+            };
+            (
+                TransactionSubtype::TransferOut,
+                StatementSection::ElectronicWithdrawals,
+                amount_cents,
+                lines,
+            )
+        }
+        _ => {
+            let amount_cents = rng.between(500, 3_500) as i64;
+            let line = format!(
+                "{} {} {}",
+                md,
+                pick(rng, FEE_DESCRIPTIONS),
+                format_cents(amount_cents, false)
+            );
+            (
+                TransactionSubtype::Fee,
+                StatementSection::Fees,
+                amount_cents,
+                vec![line],
+            )
+        }
+    };
+    ExpectedTransaction {
+        date,
+        subtype,
+        section,
+        amount_cents,
+        page: 0,
+        lines,
+    }
+}
+
+/// A line of the statement body, with the section it continues when a page breaks.
+struct BodyLine {
+    text: String,
+    section: Option<StatementSection>,
+    /// The transaction that starts on this line.
+    transaction: Option<usize>,
+}
+
+fn push_line(
+    body: &mut Vec<BodyLine>,
+    text: String,
+    section: Option<StatementSection>,
+    transaction: Option<usize>,
+) {
+    body.push(BodyLine {
+        text,
+        section,
+        transaction,
+    })
+}
+
+fn generate_statement(
+    rng: &mut XorShift64,
+    spec: &GeneratorSpec,
+    name: String,
+    period: StatementPeriod,
+    beginning_balance_cents: i64,
+) -> SyntheticStatement {
+    let period_days = (period.end - period.start).num_days() as u64;
+    let mut transactions: Vec<ExpectedTransaction> = (0..spec.transactions_per_statement)
+        .map(|_| {
+            let date = period.start + chrono::Days::new(rng.between(0, period_days));
+            generate_transaction(rng, date)
+        })
+        .collect();
+    // Listed by section in statement order, and by date within a section.
+    let sections = [
+        StatementSection::DepositsAndAdditions,
+        StatementSection::AtmAndDebitCardWithdrawals,
+        StatementSection::ElectronicWithdrawals,
+        StatementSection::Fees,
+    ];
+    transactions.sort_by_key(|transaction| {
+        let section_index = sections
+            .iter()
+            .position(|section| *section == transaction.section);
+        (section_index, transaction.date)
+    });
+    let ending_balance_cents = beginning_balance_cents
+        + transactions
+            .iter()
+            .map(ExpectedTransaction::signed_cents)
+            .sum::<i64>();
+
+    let section_total = |section: StatementSection| -> (usize, i64) {
+        let listed = transactions.iter().filter(|t| t.section == section);
+        (listed.clone().count(), listed.map(|t| t.amount_cents).sum())
+    };
+    let mut body: Vec<BodyLine> = vec![];
+    for text in [
+        String::from("CHECKING SUMMARY"),
+        String::from("INSTANCES AMOUNT"),
+        format!(
+            "Beginning Balance {}",
+            format_cents(beginning_balance_cents, true)
+        ),
+    ] {
+        push_line(&mut body, text, None, None);
+    }
+    for section in sections {
+        let (count, total_cents) = section_total(section);
+        let signed_total = match section.direction() {
+            Some(TransactionKind::Credit) => total_cents,
+            _ => -total_cents,
+        };
+        let label = section.summary_label().unwrap_or(section.heading());
+        let text = format!("{} {} {}", label, count, format_cents(signed_total, true));
+        push_line(&mut body, text, None, None);
+    }
+    let ending_line = format!(
+        "Ending Balance {}",
+        format_cents(ending_balance_cents, true)
+    );
+    push_line(&mut body, ending_line, None, None);
+
+    for section in sections {
+        let (count, total_cents) = section_total(section);
+        if count == 0 {
+            continue;
+        }
+        push_line(&mut body, String::from(section.heading()), None, None);
+        push_line(
+            &mut body,
+            String::from("DATE DESCRIPTION AMOUNT"),
+            None,
+            None,
+        );
+        for (transaction_index, transaction) in transactions.iter().enumerate() {
+            if transaction.section != section {
+                continue;
+            }
+            for (line_index, line) in transaction.lines.iter().enumerate() {
+                let starts = (line_index == 0).then_some(transaction_index);
+                push_line(&mut body, line.clone(), Some(section), starts);
+            }
+        }
+        let label = section.summary_label().unwrap_or(section.heading());
+        let total_line = format!("Total {} {}", label, format_cents(total_cents, true));
+        push_line(&mut body, total_line, None, None);
+    }
+
+    push_line(&mut body, String::from("DAILY ENDING BALANCE"), None, None);
+    let mut daily_balances: Vec<(NaiveDate, i64)> = vec![];
+    let mut by_date: Vec<&ExpectedTransaction> = transactions.iter().collect();
+    by_date.sort_by_key(|transaction| transaction.date);
+    let mut balance_cents = beginning_balance_cents;
+    for transaction in by_date {
+        balance_cents += transaction.signed_cents();
+        match daily_balances.last_mut() {
+            Some((date, day_balance)) if *date == transaction.date => *day_balance = balance_cents,
+            _ => daily_balances.push((transaction.date, balance_cents)),
+        }
+    }
+    for row in daily_balances.chunks(3) {
+        let text = row
+            .iter()
+            .map(|(date, cents)| format!("{} {}", month_day(*date), format_cents(*cents, false)))
+            .collect::<Vec<String>>()
+            .join(" ");
+        push_line(&mut body, text, None, None);
+    }
+
+    let pdf_page_strs = paginate(spec, &period, &body, &mut transactions);
+    SyntheticStatement {
+        name,
+        period,
+        beginning_balance_cents,
+        ending_balance_cents,
+        pdf_page_strs,
+        transactions,
+    }
+}
+
+/// Lays the body out on pages framed by the company and period header and the page
+/// footer. A section that continues on a new page gets a `(continued)` heading and column
+/// headings, as bank statements print them.
+fn paginate(
+    spec: &GeneratorSpec,
+    period: &StatementPeriod,
+    body: &[BodyLine],
+    transactions: &mut [ExpectedTransaction],
+) -> Vec<String> {
+    let period_line = format!(
+        "{} through {}",
+        period.start.format("%B %-d, %Y"),
+        period.end.format("%B %-d, %Y")
+    );
+    let body_lines_per_page = spec.lines_per_page - PAGE_FRAME_LINES;
+    let mut page_bodies: Vec<Vec<String>> = vec![vec![]];
+    for line in body {
+        if page_bodies.last().map_or(0, Vec::len) >= body_lines_per_page {
+            let mut next_page_body: Vec<String> = vec![];
+            if let Some(section) = line.section {
+                next_page_body.push(format!("{} (continued)", section.heading()));
+                next_page_body.push(String::from("DATE DESCRIPTION AMOUNT"));
+            }
+            page_bodies.push(next_page_body);
+        }
+        let page_body = page_bodies.last_mut().expect("there is always a page");
+        page_body.push(line.text.clone());
+        if let Some(transaction_index) = line.transaction {
+            transactions[transaction_index].page = page_bodies.len();
+        }
+    }
+    let page_count = page_bodies.len();
+    page_bodies
+        .into_iter()
+        .enumerate()
+        .map(|(page_index, page_body)| {
+            let mut page_str = format!("{}\n{}\n", spec.company, period_line);
+            for line in page_body {
+                page_str.push_str(&line);
+                page_str.push('\n');
+            }
+            page_str.push_str(&format!("Page {} of {}\n", page_index + 1, page_count));
+            page_str
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balance_utils::replay_daily_balances;
+    use crate::money_utils::Money;
+    use crate::parse_utils::{ExtractedTransactions, TransactionKindConst};
+    use crate::profile::ParsingProfile;
+    use crate::pyo3_pdf_service::embedded_python;
+    use crate::scanner::StatementScanner;
+    use crate::statement::{parse_statement_pages, read_statement_pages, PageExtractor};
+    use crate::summary_utils::summary_mismatches;
+    use fastnum::decimal::{Context, Decimal};
+    use fastnum::D256;
+
+    fn money(cents: i64) -> Money {
+        let amount: Decimal<4> =
+            D256::from_str(&format!("{}", cents), Context::default()).unwrap() / D256::from(100);
+        Money::new(amount, crate::money_utils::Currency::Usd)
+    }
+
+    /// Kind, date, amount and page of every transaction, sorted.
+    fn parsed_rows(transactions: &ExtractedTransactions) -> Vec<String> {
+        fn rows<T: TransactionKindConst>(kind: &str, transactions: &[T]) -> Vec<String> {
+            transactions
+                .iter()
+                .map(|t| {
+                    format!(
+                        "{} {} {:.2} p{}",
+                        t.date(),
+                        kind,
+                        t.amount(),
+                        t.source().page()
+                    )
+                })
+                .collect()
+        }
+        let mut parsed = rows("Deposit", &transactions.deposits);
+        parsed.extend(rows("DebitCardPurchase", &transactions.card_purchases));
+        parsed.extend(rows("OnlinePayment", &transactions.payments));
+        parsed.extend(rows("TransferOut", &transactions.transfers_out));
+        parsed.extend(rows("Fee", &transactions.fees));
+        parsed.sort();
+        parsed
+    }
+
+    fn expected_rows(statement: &SyntheticStatement) -> Vec<String> {
+        let mut expected: Vec<String> = statement
+            .transactions
+            .iter()
+            .map(|t| {
+                format!(
+                    "{} {:?} {:.2} p{}",
+                    t.date,
+                    t.subtype,
+                    money(t.amount_cents),
+                    t.page
+                )
+            })
+            .collect();
+        expected.sort();
+        expected
+    }
+
+    fn assert_parses_as_expected(statement: &SyntheticStatement, pdf_page_strs: &[String]) {
+        let scanner = StatementScanner::new(ParsingProfile::default());
+        let as_of = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        let parsed = parse_statement_pages(
+            Path::new(&format!("{}.pdf", statement.name)),
+            pdf_page_strs,
+            &scanner,
+            as_of,
+            vec![],
+        )
+        .unwrap();
+
+        assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+        assert!(parsed.is_complete(), "{:?}", parsed.page_issues);
+        assert_eq!(parsed.period, statement.period);
+        assert_eq!(
+            parsed.begin_bal,
+            Some(money(statement.beginning_balance_cents))
+        );
+        assert_eq!(
+            parsed.ending_bal,
+            Some(money(statement.ending_balance_cents))
+        );
+        assert_eq!(parsed_rows(&parsed.transactions), expected_rows(statement));
+        assert_eq!(parsed.summary_totals.len(), 4);
+        assert!(
+            summary_mismatches(&parsed.summary_totals, &parsed.transactions)
+                .unwrap()
+                .is_empty()
+        );
+        let divergences = replay_daily_balances(
+            parsed.begin_bal.unwrap(),
+            &parsed.transactions.signed_amounts(),
+            &parsed.daily_balances,
+        )
+        .unwrap();
+        assert!(divergences.is_empty(), "{:?}", divergences);
+    }
+
+    #[test]
+    fn generated_statement_text_parses_as_expected() {
+        for seed in 1..=8 {
+            let spec = GeneratorSpec::parse(&format!(
+                "seed = {}\nstatements = 3\nfirst_period_start = 2023-11-20\nlines_per_page = 18\n",
+                seed
+            ))
+            .unwrap();
+            let statements = generate_statements(&spec);
+            assert!(statements.iter().all(|s| s.pdf_page_strs.len() > 2));
+            assert!(statements[1].period.start.year() != statements[1].period.end.year());
+            for statement in &statements {
+                assert_parses_as_expected(statement, &statement.pdf_page_strs);
+            }
+        }
+    }
+
+    #[test]
+    fn same_spec_gives_same_statements() {
+        let spec = GeneratorSpec::default();
+        let first: Vec<String> = generate_statements(&spec)
+            .iter()
+            .map(SyntheticStatement::expected_csv)
+            .collect();
+        let second: Vec<String> = generate_statements(&spec)
+            .iter()
+            .map(SyntheticStatement::expected_csv)
+            .collect();
+        assert_eq!(first, second);
+        assert!(GeneratorSpec::parse("seed = x").is_err());
+        assert!(GeneratorSpec::parse("lines_per_page = 2").is_err());
+    }
+
+    /// Runs the whole pdf, pypdf, regex and totals pipeline. Needs pypdf and the
+    /// `statements_to_books` package importable by the embedded Python; run with
+    /// `cargo test generated_statement_pdf -- --ignored`.
+    #[test]
+    #[ignore]
+    fn generated_statement_pdf_parses_as_expected() {
+        let out_dir = std::env::temp_dir().join("statements_to_books_synthetic");
+        let extractor = PageExtractor::Subprocess {
+            python: embedded_python().unwrap(),
+        };
+        for statement in generate_statements(&GeneratorSpec::default()) {
+            statement.write_files(&out_dir).unwrap();
+            let pdf_file_path = out_dir.join(format!("{}.pdf", statement.name));
+            let mut warnings = vec![];
+            let pdf_page_strs =
+                read_statement_pages(&pdf_file_path, &extractor, &mut warnings).unwrap();
+            assert!(warnings.is_empty(), "{:?}", warnings);
+            assert_parses_as_expected(&statement, &pdf_page_strs);
+        }
+    }
+}
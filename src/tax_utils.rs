@@ -0,0 +1,487 @@
+use crate::ledger_utils::{AccountType, Ledger, BANK_FEES, SALES_REVENUE};
+use crate::money_utils::{Currency, Money};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// A line of a tax form that amounts are reported on.
+#[derive(Debug, PartialEq)]
+pub(crate) struct TaxLine {
+    pub number: &'static str,
+    pub description: &'static str,
+    /// Whether the line reports income rather than an expense.
+    pub income: bool,
+}
+
+const fn expense_line(number: &'static str, description: &'static str) -> TaxLine {
+    TaxLine {
+        number,
+        description,
+        income: false,
+    }
+}
+
+/// The Schedule C (Form 1040) lines a sole proprietor's categories can be mapped to: gross
+/// receipts in Part I and the expenses of Part II.
+pub(crate) static SCHEDULE_C_LINES: &[TaxLine] = &[
+    TaxLine {
+        number: "1",
+        description: "Gross receipts or sales",
+        income: true,
+    },
+    TaxLine {
+        number: "6",
+        description: "Other income",
+        income: true,
+    },
+    expense_line("8", "Advertising"),
+    expense_line("9", "Car and truck expenses"),
+    expense_line("10", "Commissions and fees"),
+    expense_line("11", "Contract labor"),
+    expense_line("12", "Depletion"),
+    expense_line("13", "Depreciation and section 179 expense"),
+    expense_line("14", "Employee benefit programs"),
+    expense_line("15", "Insurance (other than health)"),
+    expense_line("16a", "Interest, mortgage"),
+    expense_line("16b", "Interest, other"),
+    expense_line("17", "Legal and professional services"),
+    expense_line("18", "Office expense"),
+    expense_line("19", "Pension and profit-sharing plans"),
+    expense_line("20a", "Rent or lease, vehicles, machinery and equipment"),
+    expense_line("20b", "Rent or lease, other business property"),
+    expense_line("21", "Repairs and maintenance"),
+    expense_line("22", "Supplies"),
+    expense_line("23", "Taxes and licenses"),
+    expense_line("24a", "Travel"),
+    expense_line("24b", "Deductible meals"),
+    expense_line("25", "Utilities"),
+    expense_line("26", "Wages"),
+    expense_line("27a", "Other expenses"),
+];
+
+fn schedule_c_line(number: &str) -> Option<&'static TaxLine> {
+    SCHEDULE_C_LINES.iter().find(|line| line.number == number)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TaxLineMapError {
+    Io { path: String, message: String },
+    InvalidLine { line_number: usize, text: String },
+}
+
+impl fmt::Display for TaxLineMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaxLineMapError::Io { path, message } => {
+                write!(f, "tax line map not read from {}. {}", path, message)
+            }
+            TaxLineMapError::InvalidLine { line_number, text } => {
+                write!(
+                    f,
+                    "tax line map line {} not parsed: {:?}",
+                    line_number, text
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TaxLineMapError {}
+
+/// Which Schedule C line each income or expense category is reported on.
+///
+/// A map file has one `category = line` line per category, for example `Software = 27a`
+/// or `Consulting Income = 1`. Blank lines and lines starting with `#` are ignored. Sales
+/// revenue is mapped to line 1 and bank fees to line 27a unless the file says otherwise.
+#[derive(Debug, Clone)]
+pub(crate) struct TaxLineMap {
+    lines: HashMap<String, &'static TaxLine>,
+}
+
+impl Default for TaxLineMap {
+    fn default() -> Self {
+        let mut lines = HashMap::new();
+        lines.insert(String::from(SALES_REVENUE), schedule_c_line("1").unwrap());
+        lines.insert(String::from(BANK_FEES), schedule_c_line("27a").unwrap());
+        Self { lines }
+    }
+}
+
+impl TaxLineMap {
+    pub fn load(path: &Path) -> Result<Self, TaxLineMapError> {
+        let map_str = std::fs::read_to_string(path).map_err(|e| TaxLineMapError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&map_str)
+    }
+
+    pub fn parse(map_str: &str) -> Result<Self, TaxLineMapError> {
+        let mut map = TaxLineMap::default();
+        for (line_index, line) in map_str.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = || TaxLineMapError::InvalidLine {
+                line_number: line_index + 1,
+                text: String::from(line),
+            };
+            let (category, number) = line.split_once('=').ok_or_else(invalid_line)?;
+            let category = category.trim();
+            if category.is_empty() {
+                return Err(invalid_line());
+            }
+            let tax_line = schedule_c_line(number.trim()).ok_or_else(invalid_line)?;
+            map.lines.insert(String::from(category), tax_line);
+        }
+        Ok(map)
+    }
+
+    pub fn line_of(&self, category: &str) -> Option<&'static TaxLine> {
+        self.lines.get(category).copied()
+    }
+}
+
+/// The total of one Schedule C line and the categories it is made of.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TaxLineTotal {
+    pub line: &'static TaxLine,
+    pub categories: Vec<String>,
+    pub amount: Money,
+}
+
+/// Why a category's amounts are on no tax line.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum UnmappedReason {
+    NoTaxLine,
+    /// The category is mapped to an income line but is an expense account, or the reverse,
+    /// so adding it to the line would count it the wrong way.
+    WrongKindOfLine {
+        line: &'static TaxLine,
+        account_type: AccountType,
+    },
+}
+
+impl fmt::Display for UnmappedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnmappedReason::NoTaxLine => write!(f, "no tax line"),
+            UnmappedReason::WrongKindOfLine { line, account_type } => write!(
+                f,
+                "{:?} account mapped to {} line {}",
+                account_type,
+                if line.income { "income" } else { "expense" },
+                line.number
+            ),
+        }
+    }
+}
+
+/// A journal entry booked to a category that is on no tax line, which has to be
+/// categorized or mapped before the year can be filed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct UnmappedAmount {
+    pub date: NaiveDate,
+    pub category: String,
+    pub description: String,
+    /// Positive when it adds to the category.
+    pub amount: Money,
+    pub reason: UnmappedReason,
+}
+
+/// The year's income and expense categories totaled per Schedule C line.
+///
+/// Amounts are as booked from the bank account, so cost of goods sold, vehicle mileage,
+/// depreciation schedules and home office use are not accounted for.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ScheduleC {
+    pub year: i32,
+    pub currency: Currency,
+    /// Lines with any amount, in form order.
+    pub lines: Vec<TaxLineTotal>,
+    pub unmapped: Vec<UnmappedAmount>,
+}
+
+impl ScheduleC {
+    pub fn from_ledger(ledger: &Ledger, year: i32, map: &TaxLineMap) -> Self {
+        let start = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(year, 12, 31).unwrap();
+        let mut totals: Vec<TaxLineTotal> = vec![];
+        let mut unmapped_categories: Vec<(&str, UnmappedReason)> = vec![];
+        for account_type in [AccountType::Revenue, AccountType::Expense] {
+            for (account, balance) in ledger.account_balances_between(account_type, start, end) {
+                if balance.is_zero() {
+                    continue;
+                }
+                let Some(line) = map.line_of(&account.name) else {
+                    unmapped_categories.push((&account.name, UnmappedReason::NoTaxLine));
+                    continue;
+                };
+                if line.income != (account_type == AccountType::Revenue) {
+                    unmapped_categories.push((
+                        &account.name,
+                        UnmappedReason::WrongKindOfLine { line, account_type },
+                    ));
+                    continue;
+                }
+                match totals
+                    .iter_mut()
+                    .find(|total| std::ptr::eq(total.line, line))
+                {
+                    Some(total) => {
+                        total.categories.push(account.name.clone());
+                        total.amount =
+                            Money::new(total.amount.amount + balance.amount, ledger.currency);
+                    }
+                    None => totals.push(TaxLineTotal {
+                        line,
+                        categories: vec![account.name.clone()],
+                        amount: balance,
+                    }),
+                }
+            }
+        }
+        totals.sort_by_key(|total| {
+            SCHEDULE_C_LINES
+                .iter()
+                .position(|line| std::ptr::eq(line, total.line))
+        });
+
+        let mut unmapped = vec![];
+        for entry in ledger
+            .entries
+            .iter()
+            .filter(|entry| (start..=end).contains(&entry.date))
+        {
+            for posting in &entry.postings {
+                let Some((_, reason)) = unmapped_categories
+                    .iter()
+                    .find(|(category, _)| *category == posting.account)
+                else {
+                    continue;
+                };
+                let account = ledger.chart.get(&posting.account).unwrap();
+                unmapped.push(UnmappedAmount {
+                    date: entry.date,
+                    category: posting.account.clone(),
+                    description: entry.description.clone(),
+                    amount: if posting.side == account.normal_side {
+                        posting.amount
+                    } else {
+                        -posting.amount
+                    },
+                    reason: reason.clone(),
+                });
+            }
+        }
+        unmapped.sort_by_key(|unmapped| unmapped.date);
+        Self {
+            year,
+            currency: ledger.currency,
+            lines: totals,
+            unmapped,
+        }
+    }
+
+    fn total(&self, income: bool) -> Money {
+        Money::new(
+            self.lines
+                .iter()
+                .filter(|total| total.line.income == income)
+                .map(|total| total.amount.amount)
+                .sum(),
+            self.currency,
+        )
+    }
+
+    /// Lines 1 and 6.
+    pub fn gross_income(&self) -> Money {
+        self.total(true)
+    }
+
+    /// Line 28, the total of the expense lines.
+    pub fn total_expenses(&self) -> Money {
+        self.total(false)
+    }
+
+    /// Line 31, gross income less total expenses.
+    pub fn net_profit(&self) -> Money {
+        Money::new(
+            self.gross_income().amount - self.total_expenses().amount,
+            self.currency,
+        )
+    }
+
+    /// Whether every income and expense of the year is on a line of its kind.
+    pub fn is_ready_to_file(&self) -> bool {
+        self.unmapped.is_empty()
+    }
+}
+
+impl fmt::Display for ScheduleC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Schedule C for {} ({})", self.year, self.currency)?;
+        for total in &self.lines {
+            writeln!(
+                f,
+                "Line {:<4}{:<50}{:>13}",
+                total.line.number,
+                total.line.description,
+                format!("{:.2}", total.amount.amount)
+            )?;
+            writeln!(f, "          from {}", total.categories.join(", "))?;
+        }
+        writeln!(
+            f,
+            "Line 28  {:<50}{:>13}",
+            "Total expenses",
+            format!("{:.2}", self.total_expenses().amount)
+        )?;
+        writeln!(
+            f,
+            "Line 31  {:<50}{:>13}",
+            "Net profit or (loss)",
+            format!("{:.2}", self.net_profit().amount)
+        )?;
+        if self.is_ready_to_file() {
+            return Ok(());
+        }
+        writeln!(
+            f,
+            "NOT READY TO FILE, {} amounts in categories not on a tax line:",
+            self.unmapped.len()
+        )?;
+        for unmapped in &self.unmapped {
+            writeln!(
+                f,
+                "  {} {:<24}{:>13} {} ({})",
+                unmapped.date,
+                unmapped.category,
+                format!("{:.2}", unmapped.amount.amount),
+                unmapped.description,
+                unmapped.reason
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category_utils::Categorizer;
+    use crate::ledger_utils::{JournalEntry, BANK_ACCOUNT, CARD_PURCHASES};
+    use fastnum::decimal::Context;
+    use fastnum::D256;
+
+    fn usd(amount: &str) -> Money {
+        Money::new(
+            D256::from_str(amount, Context::default()).unwrap(),
+            Currency::Usd,
+        )
+    }
+
+    #[test]
+    fn totals_categories_per_line_and_lists_unmapped_amounts() {
+        let categorizer =
+            Categorizer::parse("[Software]\npayee = Github\n[Ads]\npayee = Meta\n").unwrap();
+        let mut ledger = Ledger::new(categorizer.chart_of_accounts(), Currency::Usd);
+        for ((year, month, day), debit, credit, amount) in [
+            ((2023, 12, 30), BANK_ACCOUNT, SALES_REVENUE, "700"),
+            ((2024, 1, 10), BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            ((2024, 2, 10), "Software", BANK_ACCOUNT, "50"),
+            ((2024, 3, 10), "Ads", BANK_ACCOUNT, "120"),
+            ((2024, 3, 31), BANK_FEES, BANK_ACCOUNT, "15"),
+            ((2024, 4, 2), CARD_PURCHASES, BANK_ACCOUNT, "30"),
+        ] {
+            let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+            ledger
+                .post(JournalEntry::transfer(
+                    date,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+        let map = TaxLineMap::parse("# category = line\nSoftware = 27a\nAds = 8\n").unwrap();
+
+        let schedule_c = ScheduleC::from_ledger(&ledger, 2024, &map);
+        assert_eq!(
+            schedule_c
+                .lines
+                .iter()
+                .map(|total| (total.line.number, total.amount))
+                .collect::<Vec<_>>(),
+            vec![("1", usd("1000")), ("8", usd("120")), ("27a", usd("65"))]
+        );
+        assert_eq!(schedule_c.lines[2].categories, vec![BANK_FEES, "Software"]);
+        assert_eq!(schedule_c.total_expenses(), usd("185"));
+        assert_eq!(schedule_c.net_profit(), usd("815"));
+        assert!(!schedule_c.is_ready_to_file());
+        assert_eq!(schedule_c.unmapped.len(), 1);
+        assert_eq!(schedule_c.unmapped[0].category, CARD_PURCHASES);
+        assert_eq!(schedule_c.unmapped[0].amount, usd("30"));
+        assert_eq!(schedule_c.unmapped[0].reason, UnmappedReason::NoTaxLine);
+
+        assert!(TaxLineMap::parse("Software = 99\n").is_err());
+        assert!(TaxLineMap::parse("Software 27a\n").is_err());
+    }
+
+    #[test]
+    fn lists_categories_mapped_to_the_wrong_kind_of_line() {
+        let categorizer = Categorizer::parse(
+            "[Software]\npayee = Github\n[Consulting Income]\nkind = Deposit\npayee = Globex\n",
+        )
+        .unwrap();
+        let mut ledger = Ledger::new(categorizer.chart_of_accounts(), Currency::Usd);
+        for (day, debit, credit, amount) in [
+            (10, BANK_ACCOUNT, SALES_REVENUE, "1000"),
+            (12, BANK_ACCOUNT, "Consulting Income", "400"),
+            (15, "Software", BANK_ACCOUNT, "50"),
+        ] {
+            let date = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+            ledger
+                .post(JournalEntry::transfer(
+                    date,
+                    "entry",
+                    debit,
+                    credit,
+                    usd(amount),
+                ))
+                .unwrap();
+        }
+        let map = TaxLineMap::parse("Software = 1\nConsulting Income = 27a\n").unwrap();
+
+        let schedule_c = ScheduleC::from_ledger(&ledger, 2024, &map);
+        assert_eq!(schedule_c.gross_income(), usd("1000"));
+        assert_eq!(schedule_c.total_expenses(), usd("0"));
+        assert!(!schedule_c.is_ready_to_file());
+        assert_eq!(
+            schedule_c
+                .unmapped
+                .iter()
+                .map(|unmapped| (
+                    unmapped.category.as_str(),
+                    unmapped.amount,
+                    unmapped.reason.to_string()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "Consulting Income",
+                    usd("400"),
+                    String::from("Revenue account mapped to expense line 27a")
+                ),
+                (
+                    "Software",
+                    usd("50"),
+                    String::from("Expense account mapped to income line 1")
+                ),
+            ]
+        );
+    }
+}
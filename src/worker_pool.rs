@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Applies `work` to every item on up to `jobs` threads and returns the results in the
+/// order of `items`, however the threads happen to finish.
+///
+/// Threads take the next unprocessed item as soon as they are free, so one long statement
+/// does not hold up a whole batch of short ones.
+pub(crate) fn map_in_order<T, R, F>(items: &[T], jobs: usize, work: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let jobs = jobs.clamp(1, items.len().max(1));
+    let next_index = AtomicUsize::new(0);
+    let (result_sender, result_receiver) = mpsc::channel::<(usize, R)>();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let result_sender = result_sender.clone();
+            let next_index = &next_index;
+            let work = &work;
+            scope.spawn(move || loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                if result_sender.send((index, work(item))).is_err() {
+                    break;
+                }
+            });
+        }
+    });
+    drop(result_sender);
+
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    for (index, result) in result_receiver {
+        results[index] = Some(result);
+    }
+    results
+        .into_iter()
+        .map(|result| result.expect("every item is processed by a worker"))
+        .collect()
+}
+
+/// The number of workers to use when none is given: one per available core.
+pub(crate) fn default_jobs() -> usize {
+    thread::available_parallelism()
+        .map(|jobs| jobs.get())
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn results_keep_input_order() {
+        let items: Vec<u64> = (0..40).collect();
+        let results = map_in_order(&items, 8, |item| {
+            thread::sleep(Duration::from_millis((40 - item) % 7));
+            item * 10
+        });
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 10).collect::<Vec<_>>()
+        );
+        assert!(map_in_order(&[] as &[u64], 4, |item| *item).is_empty());
+    }
+}